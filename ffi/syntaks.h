@@ -0,0 +1,105 @@
+/*
+ * syntaks, a TEI Tak engine
+ * Copyright (c) 2026 Ciekce
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+/*
+ * C interface to the syntaks engine, implemented in src/ffi.rs and built as
+ * the cdylib (libsyntaks.so, libsyntaks.dylib or syntaks.dll) by cargo build.
+ *
+ * All strings are NUL-terminated. Moves use PTN notation, positions TPS.
+ * Functions returning int return SYNTAKS_OK or one of the error codes below,
+ * and leave the engine unchanged on failure. An engine handle must not be
+ * used from more than one thread at a time.
+ */
+
+#ifndef SYNTAKS_H
+#define SYNTAKS_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define SYNTAKS_OK 0
+#define SYNTAKS_ERR_NULL 1
+#define SYNTAKS_ERR_INVALID_ARGUMENT 2
+#define SYNTAKS_ERR_INVALID_TPS 3
+#define SYNTAKS_ERR_INVALID_MOVE 4
+#define SYNTAKS_ERR_ILLEGAL_MOVE 5
+#define SYNTAKS_ERR_BUFFER_TOO_SMALL 6
+#define SYNTAKS_ERR_GAME_OVER 7
+#define SYNTAKS_ERR_NO_LIMITS 8
+#define SYNTAKS_ERR_ALLOC 9
+
+typedef struct SyntaksEngine SyntaksEngine;
+
+/* zero means no limit, but at least one limit must be set */
+typedef struct SyntaksLimits {
+    int32_t depth;
+    uint64_t nodes;
+    uint64_t movetime_ms;
+} SyntaksLimits;
+
+typedef struct SyntaksSearchResult {
+    char best_move[16];
+    /* from the side to move's perspective */
+    int32_t score;
+    /* moves until mate, negative when getting mated, 0 if not a mate score */
+    int32_t mate;
+    int32_t depth;
+    uint64_t nodes;
+    /* space-separated, cut short if it would not fit */
+    char pv[512];
+} SyntaksSearchResult;
+
+/* starts a 6x6 game with the default komi of 2. never returns NULL */
+SyntaksEngine *syntaks_engine_new(void);
+/* NULL is ignored */
+void syntaks_engine_free(SyntaksEngine *engine);
+
+/* keeps the previous hash table if the new one cannot be allocated */
+int syntaks_set_hash(SyntaksEngine *engine, size_t size_mib);
+int syntaks_set_threads(SyntaksEngine *engine, uint32_t threads);
+
+/* size 3 to 8, komi in half flats. clears the hash table */
+int syntaks_new_game(SyntaksEngine *engine, uint8_t size, uint8_t half_komi);
+
+/* tps may be NULL for the starting position, and moves NULL or a
+ * space-separated list of moves to play from it. the board size must
+ * match the one set by syntaks_new_game */
+int syntaks_set_position(SyntaksEngine *engine, const char *tps, const char *moves);
+int syntaks_apply_move(SyntaksEngine *engine, const char *move);
+
+int syntaks_get_tps(const SyntaksEngine *engine, char *buf, size_t len);
+/* space-separated, none once the game is over. count may be NULL */
+int syntaks_legal_moves(const SyntaksEngine *engine, char *buf, size_t len, size_t *count);
+
+/* blocks until the search is done */
+int syntaks_search(SyntaksEngine *engine, const SyntaksLimits *limits, SyntaksSearchResult *result);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,159 @@
+/*
+ * syntaks, a TEI Tak engine
+ * Copyright (c) 2026 Ciekce
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+/*
+ * Exercises the C interface. From the repository root:
+ *
+ *   cargo build --release
+ *   cc -std=c99 -Wall -Wextra -o target/ffi_test ffi/test.c -Iffi -Ltarget/release -lsyntaks
+ *   LD_LIBRARY_PATH=target/release ./target/ffi_test
+ *
+ * Exits with a nonzero status on the first failed check.
+ */
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "syntaks.h"
+
+static int failures = 0;
+
+#define CHECK(cond)                                                      \
+    do {                                                                 \
+        if (!(cond)) {                                                   \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, #cond); \
+            failures++;                                                  \
+        }                                                                \
+    } while (0)
+
+static int contains_move(const char *moves, const char *move) {
+    size_t len = strlen(move);
+    const char *p = moves;
+
+    while ((p = strstr(p, move)) != NULL) {
+        int starts = p == moves || p[-1] == ' ';
+        int ends = p[len] == '\0' || p[len] == ' ';
+
+        if (starts && ends) {
+            return 1;
+        }
+
+        p += len;
+    }
+
+    return 0;
+}
+
+int main(void) {
+    char buf[8192];
+    size_t count = 0;
+
+    SyntaksEngine *engine = syntaks_engine_new();
+    CHECK(engine != NULL);
+
+    CHECK(syntaks_set_hash(engine, 16) == SYNTAKS_OK);
+    CHECK(syntaks_set_threads(engine, 1) == SYNTAKS_OK);
+
+    /* null handling */
+    CHECK(syntaks_new_game(NULL, 5, 0) == SYNTAKS_ERR_NULL);
+    CHECK(syntaks_get_tps(engine, NULL, 0) == SYNTAKS_ERR_NULL);
+
+    /* game setup */
+    CHECK(syntaks_new_game(engine, 9, 0) == SYNTAKS_ERR_INVALID_ARGUMENT);
+    CHECK(syntaks_new_game(engine, 5, 0) == SYNTAKS_OK);
+
+    CHECK(syntaks_get_tps(engine, buf, sizeof(buf)) == SYNTAKS_OK);
+    CHECK(strcmp(buf, "x5/x5/x5/x5/x5 1 1") == 0);
+    CHECK(syntaks_get_tps(engine, buf, 4) == SYNTAKS_ERR_BUFFER_TOO_SMALL);
+
+    /* the first ply places a single flat of the opponent's on any square */
+    CHECK(syntaks_legal_moves(engine, buf, sizeof(buf), &count) == SYNTAKS_OK);
+    CHECK(count == 25);
+    CHECK(contains_move(buf, "a1"));
+    CHECK(!contains_move(buf, "Ca1"));
+
+    /* moves */
+    CHECK(syntaks_apply_move(engine, "a1") == SYNTAKS_OK);
+    CHECK(syntaks_apply_move(engine, "a1") == SYNTAKS_ERR_ILLEGAL_MOVE);
+    CHECK(syntaks_apply_move(engine, "zz") == SYNTAKS_ERR_INVALID_MOVE);
+    CHECK(syntaks_apply_move(engine, "e5") == SYNTAKS_OK);
+
+    CHECK(syntaks_get_tps(engine, buf, sizeof(buf)) == SYNTAKS_OK);
+    CHECK(strcmp(buf, "x4,1/x5/x5/x5/2,x4 1 2") == 0);
+
+    /* positions, which are left alone on failure */
+    CHECK(syntaks_set_position(engine, NULL, "c3 a1 c3") == SYNTAKS_ERR_ILLEGAL_MOVE);
+    CHECK(syntaks_get_tps(engine, buf, sizeof(buf)) == SYNTAKS_OK);
+    CHECK(strcmp(buf, "x4,1/x5/x5/x5/2,x4 1 2") == 0);
+
+    CHECK(syntaks_set_position(engine, "x6/x6/x6/x6/x6/x6 1 1", NULL) == SYNTAKS_ERR_INVALID_TPS);
+    CHECK(syntaks_set_position(engine, "x5/x5/x5/2,2,x3/1,1,1,1,x 1 5", NULL) == SYNTAKS_OK);
+
+    /* a road in one */
+    SyntaksLimits limits = {0, 0, 0};
+    SyntaksSearchResult result;
+
+    CHECK(syntaks_search(engine, &limits, &result) == SYNTAKS_ERR_NO_LIMITS);
+
+    limits.depth = 4;
+    CHECK(syntaks_search(engine, &limits, &result) == SYNTAKS_OK);
+    CHECK(result.mate == 1);
+    CHECK(result.depth >= 1);
+    CHECK(result.nodes > 0);
+    CHECK(strncmp(result.pv, result.best_move, strlen(result.best_move)) == 0);
+
+    printf("bestmove %s score %d mate %d depth %d nodes %llu pv %s\n", result.best_move, result.score,
+           result.mate, result.depth, (unsigned long long)result.nodes, result.pv);
+
+    /* the game is over once it is played */
+    CHECK(syntaks_apply_move(engine, result.best_move) == SYNTAKS_OK);
+    CHECK(syntaks_legal_moves(engine, buf, sizeof(buf), &count) == SYNTAKS_OK);
+    CHECK(count == 0);
+    CHECK(syntaks_search(engine, &limits, &result) == SYNTAKS_ERR_GAME_OVER);
+
+    /* a node-limited search from the start of a 6x6 game */
+    CHECK(syntaks_new_game(engine, 6, 4) == SYNTAKS_OK);
+    CHECK(syntaks_set_position(engine, NULL, "a1 f6") == SYNTAKS_OK);
+
+    limits.depth = 0;
+    limits.nodes = 20000;
+    CHECK(syntaks_search(engine, &limits, &result) == SYNTAKS_OK);
+    CHECK(result.mate == 0);
+    CHECK(strlen(result.best_move) > 0);
+    CHECK(syntaks_apply_move(engine, result.best_move) == SYNTAKS_OK);
+
+    printf("bestmove %s score %d depth %d nodes %llu pv %s\n", result.best_move, result.score, result.depth,
+           (unsigned long long)result.nodes, result.pv);
+
+    syntaks_engine_free(engine);
+    syntaks_engine_free(NULL);
+
+    if (failures > 0) {
+        fprintf(stderr, "%d checks failed\n", failures);
+        return EXIT_FAILURE;
+    }
+
+    printf("all checks passed\n");
+    return EXIT_SUCCESS;
+}
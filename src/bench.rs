@@ -0,0 +1,75 @@
+/*
+ * syntaks, a TEI Tak engine
+ * Copyright (c) 2026 Ciekce
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::board::Position;
+use crate::limit::Limits;
+use crate::search::Searcher;
+use std::str::FromStr;
+use std::time::Instant;
+
+pub const DEFAULT_BENCH_DEPTH: i32 = 10;
+
+// midgame positions from selfplay, across all the sizes that matter
+const BENCH_POSITIONS: &[&str] = &[
+    "x,1,x2/x,1,x2/x,2,2S,x/1,x2,2 1 4",
+    "2S,1,1,x/x,1,x,1/12S,2,2S,x/1,1,2,2 1 8",
+    "112S,x,1,1/112S,1,2,1/1,2,x,12S/1,1,2,2 1 13",
+    "x5/x,2,2,1,x/x,1,x,2,x/x2,1S,x2/x5 1 4",
+    "x5/x2,2,1,x/1,12,1,2,2/x,1,1S,2,2/x2,1,x2 1 8",
+    "x2,1,2,x/1,1,2,1,2/1,12,1,2,2/1,1,2,21S,2/x2,1,2,2 1 13",
+    "2S,x5/x6/x4,2,x/x3,1S,2,x/x3,1,x2/x4,1,x 1 4",
+    "2S,x5/x2,1,2,2,x/x2,2,x,2,x/x,2,1,1S,2,x/x2,1,1,1,x/x4,1,x 1 8",
+    "2S,1,1,2,x2/x,1,1,2,2,x/x,1,2,1,2,2/x,2,1,1S,2,x/x2,12S,1,1,x/x3,2,1,x 1 13",
+    "x,12S,1,2,2,x/1,1,1,2,2,x/1,1,2,1,2,2/1,2,1,2,21S,2/x,1,12S,1,1,12/x3,2,1,x 1 19",
+    "x3,1C,1,x/x3,1,x2/x5,2C/x2,2S,x3/1,x,2,2,x2/x,2,1,x3 1 6",
+    "x7/x7/x,2S,x5/1,x6/x2,2,x4/x2,1,x3,1C/x3,2,x3 1 4",
+    "x7/x7/x7/1,12S,x5/x,2,2,2,2,1,x/x2,1,1,1,x,1C/x3,2,x3 1 8",
+    "x8/x6,1,x/x8/x7,2/x7,2/x3,1S,x4/x8/x4,1,2,x2 1 4",
+    "x8/x5,1,1,x/x6,1,x/x6,1,2/x6,1,2/x3,1S,x,2,2,x/x5,2,x2/x3,2,1,2,x2 1 8",
+    "x4,1,x3/x4,1,1,1,x/x4,1,1,1,x/x4,2,1,1,2/x5,2,1,2/x3,1S,2,2,2,x/x3,2,2,2,x2/x3,2,1,2,x2 1 13",
+    "x3,1,1,1,x2/x2,1,2,1,1,1,x/x,2,1,1,1,1,1,x/x4,2,1,1,2/x4,2,2,1,2/x3,2,21S,2,2,2/x2,2,2,2,2,x2/x3,2,1,2,x2 1 19",
+];
+
+// single threaded with a fresh searcher, so node counts only change with search behaviour
+pub fn run_bench(depth: i32) {
+    let mut searcher = Searcher::new();
+
+    let mut total_nodes = 0;
+    let mut total_time = 0.0;
+
+    for &tps in BENCH_POSITIONS {
+        let pos = Position::from_str(tps).unwrap();
+
+        searcher.reset();
+
+        let start = Instant::now();
+        searcher.search(&pos, &[], Limits::new(start), depth);
+
+        total_time += start.elapsed().as_secs_f64();
+        total_nodes += searcher.nodes();
+    }
+
+    let nps = (total_nodes as f64 / total_time) as usize;
+
+    println!("{} nodes {} nps", total_nodes, nps);
+}
@@ -30,12 +30,14 @@ pub struct Bitboard {
 }
 
 impl Bitboard {
-    const MASK: u64 = (1 << Square::COUNT) - 1;
-
-    pub const UPPER_EDGE: Self = Self::from_raw(0xfc0000000);
-    pub const LOWER_EDGE: Self = Self::from_raw(0x3f);
-    pub const LEFT_EDGE: Self = Self::from_raw(0x41041041);
-    pub const RIGHT_EDGE: Self = Self::from_raw(0x820820820);
+    const MASK: u64 = u64::MAX;
+
+    // edges of the full 8x8 square layout, see `BoardSize::edge`
+    // for the edges of the board actually being played on
+    pub const UPPER_EDGE: Self = Self::from_raw(0xff00000000000000);
+    pub const LOWER_EDGE: Self = Self::from_raw(0x00000000000000ff);
+    pub const LEFT_EDGE: Self = Self::from_raw(0x0101010101010101);
+    pub const RIGHT_EDGE: Self = Self::from_raw(0x8080808080808080);
 
     #[must_use]
     pub const fn empty() -> Self {
@@ -25,23 +25,46 @@ use crate::bitboard::Bitboard;
 use crate::core::*;
 use crate::hits::find_hit_for_dir;
 use crate::keys;
-use crate::road::has_road;
+use crate::road::{RoadGroup, RoadGroups, has_road, road_completions};
 use crate::takmove::Move;
 use std::cmp::Ordering;
 use std::str::FromStr;
-use std::sync::atomic;
-use std::sync::atomic::AtomicU8;
 
-pub const DEFAULT_FLATS: u8 = 30;
 pub const MIN_FLATS: u8 = 2;
-pub const MAX_FLATS: u8 = 36;
+pub const MAX_FLATS: u8 = 63;
 
-pub const DEFAULT_CAPS: u8 = 1;
 pub const MIN_CAPS: u8 = 0;
 pub const MAX_CAPS: u8 = 4;
 
-pub static FLATS: AtomicU8 = AtomicU8::new(DEFAULT_FLATS);
-pub static CAPS: AtomicU8 = AtomicU8::new(DEFAULT_CAPS);
+pub const DEFAULT_HALF_KOMI: u8 = 4;
+pub const MAX_HALF_KOMI: u8 = 16;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct GameConfig {
+    pub size: BoardSize,
+    pub flats: u8,
+    pub caps: u8,
+    // komi in half flats, given to P2
+    pub half_komi: u8,
+}
+
+impl GameConfig {
+    #[must_use]
+    pub const fn new(size: BoardSize) -> Self {
+        Self {
+            size,
+            flats: size.default_flats(),
+            caps: size.default_caps(),
+            half_komi: DEFAULT_HALF_KOMI,
+        }
+    }
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self::new(BoardSize::DEFAULT)
+    }
+}
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
 struct Keys {
@@ -82,7 +105,7 @@ impl Keys {
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub struct Stacks {
-    players: [u64; Square::COUNT],
+    players: [u128; Square::COUNT],
     heights: [u8; Square::COUNT],
     tops: [Option<PieceType>; Square::COUNT],
     keys: Keys,
@@ -90,7 +113,7 @@ pub struct Stacks {
 
 impl Stacks {
     // all flats + cap
-    pub const MAX_HEIGHT: usize = 30 + 30 + 1;
+    pub const MAX_HEIGHT: usize = MAX_FLATS as usize * 2 + 1;
 
     #[must_use]
     pub fn is_empty(&self, sq: Square) -> bool {
@@ -117,7 +140,7 @@ impl Stacks {
     }
 
     #[must_use]
-    pub fn players(&self, sq: Square) -> u64 {
+    pub fn players(&self, sq: Square) -> u128 {
         self.players[sq.idx()]
     }
 
@@ -133,7 +156,7 @@ impl Stacks {
         let height = self.heights[sq.idx()];
         self.keys.toggle_player_key(height, player, sq);
 
-        self.players[sq.idx()] |= (player.raw() as u64) << self.heights[sq.idx()];
+        self.players[sq.idx()] |= (player.raw() as u128) << self.heights[sq.idx()];
         self.heights[sq.idx()] += 1;
         self.tops[sq.idx()] = Some(pt);
     }
@@ -141,7 +164,7 @@ impl Stacks {
     fn take(&mut self, sq: Square, count: u8) -> (u8, PieceType, Option<Player>) {
         debug_assert!(count <= self.heights[sq.idx()]);
         debug_assert!(count > 0);
-        debug_assert!(count <= 6);
+        debug_assert!(count <= Position::CARRY_LIMIT);
 
         let players = (self.players[sq.idx()] >> (self.heights[sq.idx()] - count)) & ((1 << count) - 1);
         let top = self.tops[sq.idx()].unwrap();
@@ -170,6 +193,24 @@ impl Stacks {
         }
     }
 
+    // these two leave the keys alone, unmaking a move restores them wholesale
+    fn pop_raw(&mut self, sq: Square, count: u8) -> u128 {
+        let new_height = self.heights[sq.idx()] - count;
+        let players = (self.players[sq.idx()] >> new_height) & ((1 << count) - 1);
+
+        self.players[sq.idx()] &= (1 << new_height) - 1;
+        self.heights[sq.idx()] = new_height;
+        self.tops[sq.idx()] = if new_height == 0 { None } else { Some(PieceType::Flat) };
+
+        players
+    }
+
+    fn push_raw(&mut self, sq: Square, count: u8, players: u128, top: PieceType) {
+        self.players[sq.idx()] |= players << self.heights[sq.idx()];
+        self.heights[sq.idx()] += count;
+        self.tops[sq.idx()] = Some(top);
+    }
+
     fn regen_key(&mut self, occ: Bitboard) {
         self.keys.reset();
 
@@ -199,7 +240,7 @@ impl Stacks {
 impl Default for Stacks {
     fn default() -> Self {
         Self {
-            players: [u64::default(); Square::COUNT],
+            players: [u128::default(); Square::COUNT],
             heights: [u8::default(); Square::COUNT],
             tops: [None; Square::COUNT],
             keys: Default::default(),
@@ -208,7 +249,7 @@ impl Default for Stacks {
 }
 
 pub struct StackIterator {
-    players: u64,
+    players: u128,
     height: u8,
     idx: u8,
 }
@@ -227,6 +268,20 @@ impl Iterator for StackIterator {
     }
 }
 
+// what `Position::make_move` overwrites and `Position::unmake_move` can't cheaply work out again
+#[derive(Copy, Clone, Debug)]
+pub struct MoveUndo {
+    mv: Move,
+    players: [Bitboard; Player::COUNT],
+    pieces: [Bitboard; PieceType::COUNT],
+    flats_in_hand: [u8; Player::COUNT],
+    caps_in_hand: [u8; Player::COUNT],
+    keys: Keys,
+    road_groups: [RoadGroups; Player::COUNT],
+    #[cfg(debug_assertions)]
+    prev: Position,
+}
+
 pub enum FlatCountOutcome {
     None,
     Draw,
@@ -235,6 +290,7 @@ pub enum FlatCountOutcome {
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub struct Position {
+    config: GameConfig,
     stacks: Stacks,
     players: [Bitboard; Player::COUNT],
     pieces: [Bitboard; PieceType::COUNT],
@@ -243,43 +299,48 @@ pub struct Position {
     stm: Player,
     ply: u16,
     player_key: u64,
+    road_groups: [RoadGroups; Player::COUNT],
 }
 
 impl Position {
-    pub const CARRY_LIMIT: u8 = 6;
-    pub const KOMI: u32 = 2;
+    // largest carry limit of any board size, spread patterns are encoded relative to this
+    pub const CARRY_LIMIT: u8 = BoardSize::MAX;
 
     #[must_use]
-    pub fn startpos() -> Self {
+    pub fn startpos(config: GameConfig) -> Self {
         Self {
+            config,
             stacks: Stacks::default(),
             players: [Bitboard::empty(); Player::COUNT],
             pieces: [Bitboard::empty(); PieceType::COUNT],
-            flats_in_hand: [FLATS.load(atomic::Ordering::Relaxed); Player::COUNT],
-            caps_in_hand: [CAPS.load(atomic::Ordering::Relaxed); Player::COUNT],
+            flats_in_hand: [config.flats; Player::COUNT],
+            caps_in_hand: [config.caps; Player::COUNT],
             stm: Player::P1,
             ply: 0,
             player_key: 0,
+            road_groups: [RoadGroups::default(); Player::COUNT],
         }
     }
 
-    pub fn from_tps_parts(parts: &[&str]) -> Result<Self, TpsError> {
+    pub fn from_tps_parts(config: GameConfig, parts: &[&str]) -> Result<Self, TpsError> {
         if parts.len() < 2 || parts.len() > 3 {
             return Err(TpsError::WrongNumberOfParts);
         }
 
+        let size = config.size.raw() as u32;
+
         let ranks: Vec<&str> = parts[0].split('/').collect();
-        if ranks.len() != 6 {
+        if ranks.len() != size as usize {
             return Err(TpsError::WrongNumberOfRanks);
         }
 
-        let mut pos = Self::startpos();
+        let mut pos = Self::startpos(config);
 
-        for rank_idx in 0..6 {
+        for rank_idx in 0..size {
             let mut file_idx = 0;
 
-            for stack in ranks[5 - rank_idx as usize].split(',') {
-                if file_idx >= 6 {
+            for stack in ranks[(size - 1 - rank_idx) as usize].split(',') {
+                if file_idx >= size {
                     return Err(TpsError::WrongNumberOfFiles);
                 }
 
@@ -320,6 +381,10 @@ impl Position {
                         }
                     }
 
+                    if players.len() > Stacks::MAX_HEIGHT {
+                        return Err(TpsError::TooManyPieces);
+                    }
+
                     let top = top.unwrap_or(PieceType::Flat);
 
                     for (idx, &player) in players.iter().enumerate() {
@@ -334,7 +399,7 @@ impl Position {
                 }
             }
 
-            if file_idx > 6 {
+            if file_idx > size {
                 return Err(TpsError::WrongNumberOfFiles);
             }
         }
@@ -352,11 +417,28 @@ impl Position {
             }
         }
 
-        pos.regen();
+        if !pos.regen() {
+            return Err(TpsError::TooManyPieces);
+        }
 
         Ok(pos)
     }
 
+    #[must_use]
+    pub fn config(&self) -> GameConfig {
+        self.config
+    }
+
+    #[must_use]
+    pub fn size(&self) -> BoardSize {
+        self.config.size
+    }
+
+    #[must_use]
+    pub fn half_komi(&self) -> u8 {
+        self.config.half_komi
+    }
+
     #[must_use]
     pub fn stm(&self) -> Player {
         self.stm
@@ -387,6 +469,11 @@ impl Position {
         self.players[0] | self.players[1]
     }
 
+    #[must_use]
+    pub fn empty_squares(&self) -> Bitboard {
+        self.size().board() & !self.occ()
+    }
+
     #[must_use]
     pub fn flats_in_hand(&self, player: Player) -> u8 {
         self.flats_in_hand[player.idx()]
@@ -454,7 +541,147 @@ impl Position {
 
     #[must_use]
     pub fn has_road(&self, player: Player) -> bool {
-        has_road(self.roads(player))
+        self.road_groups[player.idx()].has_road()
+    }
+
+    // connected groups of road squares
+    pub fn road_groups(&self, player: Player) -> impl Iterator<Item = RoadGroup> {
+        self.road_groups[player.idx()].iter(self.roads(player))
+    }
+
+    #[must_use]
+    pub fn road_group_count(&self, player: Player) -> u32 {
+        self.road_groups[player.idx()].count()
+    }
+
+    #[must_use]
+    pub fn road_group(&self, player: Player, sq: Square) -> Option<RoadGroup> {
+        let roads = self.roads(player);
+        roads.has_sq(sq).then(|| RoadGroup::containing(sq, roads))
+    }
+
+    // empty squares where a flat or capstone would complete a road for this player,
+    // whether or not it is their turn
+    #[must_use]
+    pub fn placement_threats(&self, player: Player) -> Bitboard {
+        if self.has_no_more_pieces(player) {
+            return Bitboard::empty();
+        }
+
+        road_completions(self.roads(player), self.empty_squares(), self.size())
+    }
+
+    // sources of spreads in each direction that might complete a road for this player.
+    // a spread can only turn the squares it moves over into road squares, so only
+    // spreads that would win if all of them did are worth generating and checking
+    #[must_use]
+    fn spread_threat_candidates(&self, player: Player) -> [Bitboard; 4] {
+        let size = self.size();
+        let roads = self.roads(player);
+
+        let blockers = self.all_blockers();
+        let walls = self.piece_bb(PieceType::Wall);
+
+        let mut candidates = [Bitboard::empty(); 4];
+
+        for src in self.player_bb(player) {
+            let is_cap = self.stacks.top(src) == Some(PieceType::Capstone);
+            let max_len = self.stacks.height(src).min(size.carry_limit());
+
+            for dir in [Direction::Up, Direction::Down, Direction::Left, Direction::Right] {
+                let mut covered = roads;
+                let mut sq = src;
+
+                for _ in 0..max_len {
+                    let Some(next) = sq.shift_checked(dir).filter(|&next| size.board().has_sq(next)) else {
+                        break;
+                    };
+
+                    if blockers.has_sq(next) && !(is_cap && walls.has_sq(next)) {
+                        break;
+                    }
+
+                    covered.set_sq(next);
+                    sq = next;
+
+                    if walls.has_sq(next) {
+                        break;
+                    }
+                }
+
+                if sq != src && has_road(covered, size) {
+                    candidates[dir.idx()].set_sq(src);
+                }
+            }
+        }
+
+        candidates
+    }
+
+    // calls found with each spread completing a road for this player, until it returns false.
+    // candidates are rare, so their spreads are tried directly instead of generating every move
+    fn for_each_spread_threat(&self, player: Player, mut found: impl FnMut(Move) -> bool) {
+        let candidates = self.spread_threat_candidates(player);
+
+        if candidates.iter().all(|candidates| candidates.is_empty()) {
+            return;
+        }
+
+        let mut pos = *self;
+        if pos.stm() != player {
+            pos.make_nullmove();
+        }
+
+        for dir in [Direction::Up, Direction::Down, Direction::Left, Direction::Right] {
+            for src in candidates[dir.idx()] {
+                for pattern in 1..(1 << Self::CARRY_LIMIT) {
+                    let mv = Move::spread(src, dir, pattern);
+
+                    if !pos.is_legal(mv) {
+                        continue;
+                    }
+
+                    let undo = pos.make_move(mv);
+                    let road = pos.has_road(player);
+                    pos.unmake_move(&undo);
+
+                    if road && !found(mv) {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    // spreads that would complete a road for this player if it were their turn
+    #[must_use]
+    pub fn spread_threats(&self, player: Player) -> Vec<Move> {
+        let mut threats = Vec::new();
+
+        self.for_each_spread_threat(player, |mv| {
+            threats.push(mv);
+            true
+        });
+
+        threats
+    }
+
+    #[must_use]
+    pub fn has_spread_threat(&self, player: Player) -> bool {
+        let mut threat = false;
+
+        self.for_each_spread_threat(player, |_| {
+            threat = true;
+            false
+        });
+
+        threat
+    }
+
+    // whether this player could win by road in one move if it were their turn
+    #[must_use]
+    pub fn has_road_threat(&self, player: Player) -> bool {
+        !self.placement_threats(player).is_empty() || self.has_spread_threat(player)
     }
 
     #[must_use]
@@ -462,22 +689,27 @@ impl Position {
         self.flats_in_hand(player) == 0 && self.caps_in_hand(player) == 0
     }
 
+    // in whole flats, any half flat of komi is dropped
     #[must_use]
     pub fn fcd(&self, player: Player) -> i32 {
         let p1_advantage = self.player_piece_bb(Piece::P1Flat).popcount() as i32
             - self.player_piece_bb(Piece::P2Flat).popcount() as i32
-            - Self::KOMI as i32;
+            - (self.half_komi() / 2) as i32;
         p1_advantage * player.sign()
     }
 
     #[must_use]
     pub fn count_flats(&self) -> FlatCountOutcome {
-        if !(!self.occ()).is_empty() && !self.has_no_more_pieces(Player::P1) && !self.has_no_more_pieces(Player::P2) {
+        if !self.empty_squares().is_empty()
+            && !self.has_no_more_pieces(Player::P1)
+            && !self.has_no_more_pieces(Player::P2)
+        {
             return FlatCountOutcome::None;
         }
 
-        let p1_flats = self.player_piece_bb(Piece::P1Flat).popcount();
-        let p2_flats = self.player_piece_bb(Piece::P2Flat).popcount() + Self::KOMI;
+        // compared in half flats
+        let p1_flats = self.player_piece_bb(Piece::P1Flat).popcount() * 2;
+        let p2_flats = self.player_piece_bb(Piece::P2Flat).popcount() * 2 + self.half_komi() as u32;
 
         match p1_flats.cmp(&p2_flats) {
             Ordering::Less => FlatCountOutcome::Win(Player::P2),
@@ -488,6 +720,10 @@ impl Position {
 
     #[must_use]
     pub fn is_legal(&self, mv: Move) -> bool {
+        if !self.size().contains(mv.sq()) {
+            return false;
+        }
+
         if mv.is_spread() {
             if self.ply < 2 {
                 return false;
@@ -499,13 +735,13 @@ impl Position {
 
             let pattern = mv.pattern();
 
-            let taken = 6 - pattern.trailing_zeros();
-            if taken > self.stacks.height(mv.sq()) as u32 {
+            let taken = Self::CARRY_LIMIT as u32 - pattern.trailing_zeros();
+            if taken > self.stacks.height(mv.sq()) as u32 || taken > self.size().carry_limit() as u32 {
                 return false;
             }
 
             let dist = pattern.count_ones() as u8;
-            let (max_dist, hit_sq) = find_hit_for_dir(self.all_blockers(), mv.sq(), mv.dir());
+            let (max_dist, hit_sq) = find_hit_for_dir(self.all_blockers(), mv.sq(), mv.dir(), self.size());
 
             if dist > max_dist {
                 return false;
@@ -553,6 +789,24 @@ impl Position {
     #[must_use]
     pub fn apply_move(&self, mv: Move) -> Self {
         let mut new_pos = *self;
+        let _ = new_pos.make_move(mv);
+        new_pos
+    }
+
+    // applies `mv` in place, the returned record takes it back again with `unmake_move`
+    #[must_use]
+    pub fn make_move(&mut self, mv: Move) -> MoveUndo {
+        let undo = MoveUndo {
+            mv,
+            players: self.players,
+            pieces: self.pieces,
+            flats_in_hand: self.flats_in_hand,
+            caps_in_hand: self.caps_in_hand,
+            keys: self.stacks.keys,
+            road_groups: self.road_groups,
+            #[cfg(debug_assertions)]
+            prev: *self,
+        };
 
         if mv.is_spread() {
             debug_assert_ne!(self.stacks.top(mv.sq()), None);
@@ -563,10 +817,12 @@ impl Position {
             let dir = mv.dir();
 
             let dropped = pattern.trailing_zeros();
-            let taken = 6 - dropped;
+            let taken = Self::CARRY_LIMIT as u32 - dropped;
+
+            let prev_dst_top = self.stacks.top(mv.spread_dest());
 
             let mut pattern = pattern >> dropped;
-            let (mut players, top, new_top_player) = new_pos.stacks.take(mv.sq(), taken as u8);
+            let (mut players, top, new_top_player) = self.stacks.take(mv.sq(), taken as u8);
 
             let mut new_flats_bb = Bitboard::empty();
             let mut new_player_bbs = [Bitboard::empty(); Player::COUNT];
@@ -575,11 +831,11 @@ impl Position {
                 new_player_bbs[new_top_player.idx()].set_sq(mv.sq());
                 new_flats_bb.set_sq(mv.sq());
             } else {
-                new_pos.players[self.stm().idx()].toggle_sq(mv.sq());
+                self.players[self.stm().idx()].toggle_sq(mv.sq());
             }
 
             if top != PieceType::Flat || new_top_player.is_none() {
-                new_pos.pieces[top.idx()].toggle_sq(mv.sq());
+                self.pieces[top.idx()].toggle_sq(mv.sq());
             }
 
             let mut sq = mv.sq().shift(dir).unwrap();
@@ -588,7 +844,7 @@ impl Position {
                 let player = Player::from_raw(players & 0x1).unwrap();
                 let pt = if idx == taken - 1 { top } else { PieceType::Flat };
 
-                new_pos.stacks.push(sq, pt, player);
+                self.stacks.push(sq, pt, player);
 
                 pattern >>= 1;
                 players >>= 1;
@@ -606,37 +862,36 @@ impl Position {
             debug_assert_eq!(new_player_bbs[0] & new_player_bbs[1], Bitboard::empty());
 
             for player in 0..Player::COUNT {
-                new_pos.players[player] =
-                    (new_pos.players[player] | new_player_bbs[player]) & !new_player_bbs[player ^ 0x1];
+                self.players[player] = (self.players[player] | new_player_bbs[player]) & !new_player_bbs[player ^ 0x1];
             }
 
-            new_pos.pieces[top.idx()].set_sq(sq);
-            new_pos.pieces[PieceType::Flat.idx()] |= new_flats_bb;
+            self.pieces[top.idx()].set_sq(sq);
+            self.pieces[PieceType::Flat.idx()] |= new_flats_bb;
 
-            if let Some(prev_dst_top) = self.stacks.top(sq)
+            if let Some(prev_dst_top) = prev_dst_top
                 && prev_dst_top != top
             {
-                new_pos.pieces[prev_dst_top.idx()].clear_sq(sq);
-                new_pos.pieces[top.idx()].set_sq(sq);
+                self.pieces[prev_dst_top.idx()].clear_sq(sq);
+                self.pieces[top.idx()].set_sq(sq);
             }
 
             debug_assert_eq!(
-                new_pos.pieces[PieceType::Flat.idx()]
-                    & new_pos.pieces[PieceType::Wall.idx()]
-                    & new_pos.pieces[PieceType::Capstone.idx()],
+                self.pieces[PieceType::Flat.idx()]
+                    & self.pieces[PieceType::Wall.idx()]
+                    & self.pieces[PieceType::Capstone.idx()],
                 Bitboard::empty()
             );
 
             debug_assert_eq!(
-                new_pos.players[Player::P1.idx()] & new_pos.players[Player::P2.idx()],
+                self.players[Player::P1.idx()] & self.players[Player::P2.idx()],
                 Bitboard::empty()
             );
 
             debug_assert_eq!(
-                new_pos.pieces[PieceType::Flat.idx()]
-                    | new_pos.pieces[PieceType::Wall.idx()]
-                    | new_pos.pieces[PieceType::Capstone.idx()],
-                new_pos.players[Player::P1.idx()] | new_pos.players[Player::P2.idx()]
+                self.pieces[PieceType::Flat.idx()]
+                    | self.pieces[PieceType::Wall.idx()]
+                    | self.pieces[PieceType::Capstone.idx()],
+                self.players[Player::P1.idx()] | self.players[Player::P2.idx()]
             );
         } else {
             debug_assert_eq!(self.stacks.top(mv.sq()), None);
@@ -644,30 +899,87 @@ impl Position {
 
             let dropped_player = if self.ply() < 2 { self.stm().flip() } else { self.stm() };
 
-            new_pos.stacks.push(mv.sq(), mv.pt(), dropped_player);
+            self.stacks.push(mv.sq(), mv.pt(), dropped_player);
 
-            new_pos.players[dropped_player.idx()].set_sq(mv.sq());
-            new_pos.pieces[mv.pt().idx()].set_sq(mv.sq());
+            self.players[dropped_player.idx()].set_sq(mv.sq());
+            self.pieces[mv.pt().idx()].set_sq(mv.sq());
 
             match mv.pt() {
-                PieceType::Capstone => new_pos.caps_in_hand[dropped_player.idx()] -= 1,
-                _ => new_pos.flats_in_hand[dropped_player.idx()] -= 1,
+                PieceType::Capstone => self.caps_in_hand[dropped_player.idx()] -= 1,
+                _ => self.flats_in_hand[dropped_player.idx()] -= 1,
             }
         }
 
-        new_pos.stm = new_pos.stm.flip();
-        new_pos.ply += 1;
+        for player in [Player::P1, Player::P2] {
+            let before = undo.players[player.idx()] & !undo.pieces[PieceType::Wall.idx()];
+            self.road_groups[player.idx()].update(before, self.roads(player), self.size());
+        }
 
-        new_pos.player_key ^= keys::p2_key();
+        self.stm = self.stm.flip();
+        self.ply += 1;
+
+        self.player_key ^= keys::p2_key();
 
         #[cfg(debug_assertions)]
         {
-            let mut other_new = new_pos;
-            other_new.regen();
-            assert_eq!(new_pos, other_new);
+            let mut other = *self;
+            assert!(other.regen());
+            assert_eq!(*self, other);
         }
 
-        new_pos
+        undo
+    }
+
+    pub fn unmake_move(&mut self, undo: &MoveUndo) {
+        let mv = undo.mv;
+
+        if mv.is_spread() {
+            let dst = mv.spread_dest();
+            let back = mv.dir().flip();
+
+            let pattern = mv.pattern();
+
+            let dropped = pattern.trailing_zeros();
+            let taken = Self::CARRY_LIMIT as u32 - dropped;
+
+            let pattern = pattern >> dropped;
+            let top = self.stacks.top(dst).unwrap();
+
+            // pick the pieces back up from the far end, same pattern walk as above
+            let mut sq = dst;
+            let mut players = 0;
+
+            for idx in (0..taken).rev() {
+                players = (players << 1) | self.stacks.pop_raw(sq, 1);
+
+                if idx > 0 && (pattern >> idx) & 0x1 != 0 {
+                    sq = sq.shift(back).unwrap();
+                }
+            }
+
+            self.stacks.push_raw(mv.sq(), taken as u8, players, top);
+
+            if undo.pieces[PieceType::Wall.idx()].has_sq(dst) {
+                self.stacks.tops[dst.idx()] = Some(PieceType::Wall);
+            }
+        } else {
+            self.stacks.pop_raw(mv.sq(), 1);
+        }
+
+        self.players = undo.players;
+        self.pieces = undo.pieces;
+        self.flats_in_hand = undo.flats_in_hand;
+        self.caps_in_hand = undo.caps_in_hand;
+        self.stacks.keys = undo.keys;
+        self.road_groups = undo.road_groups;
+
+        self.stm = self.stm.flip();
+        self.ply -= 1;
+
+        self.player_key ^= keys::p2_key();
+
+        #[cfg(debug_assertions)]
+        assert_eq!(*self, undo.prev);
     }
 
     #[must_use]
@@ -682,21 +994,38 @@ impl Position {
         new_pos
     }
 
+    // a nullmove only flips the side to move, so it is its own undo
+    pub fn make_nullmove(&mut self) {
+        self.stm = self.stm.flip();
+        self.ply += 1;
+
+        self.player_key ^= keys::p2_key();
+    }
+
+    pub fn unmake_nullmove(&mut self) {
+        self.stm = self.stm.flip();
+        self.ply -= 1;
+
+        self.player_key ^= keys::p2_key();
+    }
+
     #[must_use]
     pub fn tps(&self) -> String {
+        let size = self.size().raw() as u32;
+
         let mut tps = String::with_capacity(21);
 
-        for rank in (0..6).rev() {
+        for rank in (0..size).rev() {
             let mut groups = Vec::new();
 
             let mut file = 0;
-            while file < 6 {
+            while file < size {
                 let sq = Square::from_file_rank(file, rank).unwrap();
 
                 if self.stacks.is_empty(sq) {
                     let mut empty = 1;
 
-                    while file < 5 && self.stacks.is_empty(Square::from_file_rank(file + 1, rank).unwrap()) {
+                    while file < size - 1 && self.stacks.is_empty(Square::from_file_rank(file + 1, rank).unwrap()) {
                         file += 1;
                         empty += 1;
                     }
@@ -707,22 +1036,7 @@ impl Position {
                         groups.push("x".to_owned());
                     }
                 } else {
-                    let mut stack_str = String::with_capacity(self.stacks.height(sq) as usize + 1);
-
-                    for player in self.stacks.iter(sq) {
-                        match player {
-                            Player::P1 => stack_str.push('1'),
-                            Player::P2 => stack_str.push('2'),
-                        }
-                    }
-
-                    match self.stacks.top(sq).unwrap() {
-                        PieceType::Flat => {}
-                        PieceType::Wall => stack_str.push('S'),
-                        PieceType::Capstone => stack_str.push('C'),
-                    }
-
-                    groups.push(stack_str);
+                    groups.push(self.stack_str(sq));
                 }
 
                 file += 1;
@@ -745,16 +1059,133 @@ impl Position {
         tps
     }
 
-    fn regen(&mut self) {
+    // stack contents bottom to top in tps notation, e.g. "1212S"
+    fn stack_str(&self, sq: Square) -> String {
+        let mut stack_str = String::with_capacity(self.stacks.height(sq) as usize + 1);
+
+        for player in self.stacks.iter(sq) {
+            match player {
+                Player::P1 => stack_str.push('1'),
+                Player::P2 => stack_str.push('2'),
+            }
+        }
+
+        match self.stacks.top(sq) {
+            None | Some(PieceType::Flat) => {}
+            Some(PieceType::Wall) => stack_str.push('S'),
+            Some(PieceType::Capstone) => stack_str.push('C'),
+        }
+
+        stack_str
+    }
+
+    // ascii board for humans - each cell holds the top piece and stack height,
+    // empty squares completing a road for a player are marked with '*' and the player
+    #[must_use]
+    pub fn render(&self) -> String {
+        const CELL_WIDTH: usize = 6;
+
+        let size = self.size().raw() as u32;
+
+        // nothing left to threaten once a player has a road
+        let threats = |player| {
+            if self.has_road(player) {
+                Bitboard::empty()
+            } else {
+                self.placement_threats(player)
+            }
+        };
+
+        let p1_threats = threats(Player::P1);
+        let p2_threats = threats(Player::P2);
+
+        let separator = format!("   +{}\n", format!("{}+", "-".repeat(CELL_WIDTH)).repeat(size as usize));
+
+        let mut out = String::new();
+        let mut stacks = Vec::new();
+
+        out.push_str(&separator);
+
+        for rank in (0..size).rev() {
+            out.push_str(&format!("{:>2} |", rank + 1));
+
+            for file in 0..size {
+                let sq = Square::from_file_rank(file, rank).unwrap();
+
+                let cell = if let Some(top) = self.stacks.top(sq) {
+                    let height = self.stacks.height(sq);
+
+                    let mut cell = match self.stacks.top_player(sq).unwrap() {
+                        Player::P1 => "1".to_owned(),
+                        Player::P2 => "2".to_owned(),
+                    };
+
+                    match top {
+                        PieceType::Flat => {}
+                        PieceType::Wall => cell.push('S'),
+                        PieceType::Capstone => cell.push('C'),
+                    }
+
+                    if height > 1 {
+                        cell.push_str(&format!("/{}", height));
+                        stacks.push(format!("{}: {}", sq, self.stack_str(sq)));
+                    }
+
+                    cell
+                } else {
+                    match (p1_threats.has_sq(sq), p2_threats.has_sq(sq)) {
+                        (true, true) => "*12".to_owned(),
+                        (true, false) => "*1".to_owned(),
+                        (false, true) => "*2".to_owned(),
+                        (false, false) => String::new(),
+                    }
+                };
+
+                out.push_str(&format!("{:^width$}|", cell, width = CELL_WIDTH));
+            }
+
+            out.push('\n');
+            out.push_str(&separator);
+        }
+
+        let mut files = "   ".to_owned();
+        for file in 0..size {
+            files.push_str(&format!(" {:^width$}", (b'a' + file as u8) as char, width = CELL_WIDTH));
+        }
+        out.push_str(files.trim_end());
+        out.push('\n');
+
+        if !stacks.is_empty() {
+            out.push_str(&format!("\nStacks (bottom to top): {}\n", stacks.join(", ")));
+        }
+
+        out.push('\n');
+
+        for player in [Player::P1, Player::P2] {
+            out.push_str(&format!(
+                "{:?} reserves: {} flats, {} caps\n",
+                player,
+                self.flats_in_hand(player),
+                self.caps_in_hand(player)
+            ));
+        }
+
+        out.push_str(&format!("Side to move: {:?}\n", self.stm()));
+        out.push_str(&format!("Ply: {}", self.ply()));
+
+        out
+    }
+
+    // returns false if the stacks contain more pieces than the reserves allow
+    #[must_use]
+    fn regen(&mut self) -> bool {
         self.players.fill(Bitboard::empty());
         self.pieces.fill(Bitboard::empty());
 
-        self.flats_in_hand.fill(30);
-        self.caps_in_hand.fill(1);
-
-        for sq_idx in 0..Square::COUNT {
-            let sq = Square::from_raw(sq_idx as u8).unwrap();
+        let mut flats_used = [0u32; Player::COUNT];
+        let mut caps_used = [0u32; Player::COUNT];
 
+        for sq in self.size().squares() {
             if self.stacks.is_empty(sq) {
                 continue;
             }
@@ -766,25 +1197,40 @@ impl Position {
             self.pieces[top.idx()].set_sq(sq);
 
             if top == PieceType::Capstone {
-                self.caps_in_hand[player.idx()] -= 1;
+                caps_used[player.idx()] += 1;
             } else {
-                self.flats_in_hand[player.idx()] -= 1;
+                flats_used[player.idx()] += 1;
             }
 
             let players = self.stacks.players(sq);
             let covered = (1 << (self.stacks.height(sq) - 1)) - 1;
 
-            self.flats_in_hand[0] -= (!players & covered).count_ones() as u8;
-            self.flats_in_hand[1] -= (players & covered).count_ones() as u8;
+            flats_used[0] += (!players & covered).count_ones();
+            flats_used[1] += (players & covered).count_ones();
+        }
+
+        for player in 0..Player::COUNT {
+            if flats_used[player] > self.config.flats as u32 || caps_used[player] > self.config.caps as u32 {
+                return false;
+            }
+
+            self.flats_in_hand[player] = self.config.flats - flats_used[player] as u8;
+            self.caps_in_hand[player] = self.config.caps - caps_used[player] as u8;
         }
 
         self.stacks.regen_key(self.occ());
 
+        for player in [Player::P1, Player::P2] {
+            self.road_groups[player.idx()].rebuild(self.roads(player), self.size());
+        }
+
         if self.stm() == Player::P2 {
             self.player_key = keys::p2_key();
         } else {
             self.player_key = 0;
         }
+
+        true
     }
 }
 
@@ -799,13 +1245,22 @@ pub enum TpsError {
     WrongNumberOfFiles,
     InvalidStm,
     InvalidFullmove,
+    TooManyPieces,
 }
 
 impl FromStr for Position {
     type Err = TpsError;
 
+    // infers the board size from the number of ranks, using standard reserves for it
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let parts: Vec<&str> = s.split_ascii_whitespace().collect();
-        Self::from_tps_parts(&parts)
+
+        let rank_count = parts.first().map_or(0, |board| board.split('/').count());
+        let size = u8::try_from(rank_count)
+            .ok()
+            .and_then(BoardSize::new)
+            .ok_or(TpsError::WrongNumberOfRanks)?;
+
+        Self::from_tps_parts(GameConfig::new(size), &parts)
     }
 }
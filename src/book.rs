@@ -0,0 +1,271 @@
+/*
+ * syntaks, a TEI Tak engine
+ * Copyright (c) 2026 Ciekce
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::board::Position;
+use crate::core::BoardSize;
+use crate::ptn::{PtnGame, split_games};
+use crate::takmove::Move;
+use crate::util::rng::Sfc64;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+pub const DEFAULT_BOOK_DEPTH: u16 = 16;
+pub const MAX_BOOK_DEPTH: u16 = 1024;
+
+pub const DEFAULT_BOOK_MIN_GAMES: u32 = 2;
+
+// little endian:
+//   4 byte magic, u8 version, u8 board size, 2 bytes reserved
+//   then entries sorted by key: u64 key, u16 move, u16 weight
+const MAGIC: [u8; 4] = *b"STBK";
+const VERSION: u8 = 1;
+
+const HEADER_SIZE: usize = 8;
+const ENTRY_SIZE: usize = 12;
+
+#[derive(Copy, Clone, Debug)]
+struct BookEntry {
+    key: u64,
+    mv: Move,
+    weight: u16,
+}
+
+#[derive(Debug)]
+pub enum BookError {
+    Io(std::io::Error),
+    InvalidHeader,
+    WrongSize(usize),
+    // the index of the offending entry
+    InvalidEntry(usize),
+}
+
+impl Display for BookError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{}", err),
+            Self::InvalidHeader => write!(f, "invalid header"),
+            Self::WrongSize(size) => write!(f, "wrong file size {} (not a whole number of entries)", size),
+            Self::InvalidEntry(idx) => write!(f, "invalid entry {}", idx),
+        }
+    }
+}
+
+// keys do not include the board size, so a book only covers one
+pub struct Book {
+    size: BoardSize,
+    entries: Vec<BookEntry>,
+}
+
+impl Book {
+    pub fn load(path: &str) -> Result<Self, BookError> {
+        let bytes = std::fs::read(path).map_err(BookError::Io)?;
+
+        if bytes.len() < HEADER_SIZE || bytes[0..4] != MAGIC || bytes[4] != VERSION {
+            return Err(BookError::InvalidHeader);
+        }
+
+        let size = BoardSize::new(bytes[5]).ok_or(BookError::InvalidHeader)?;
+
+        let data = &bytes[HEADER_SIZE..];
+
+        if !data.len().is_multiple_of(ENTRY_SIZE) {
+            return Err(BookError::WrongSize(bytes.len()));
+        }
+
+        let mut entries = Vec::with_capacity(data.len() / ENTRY_SIZE);
+
+        for (idx, entry) in data.chunks_exact(ENTRY_SIZE).enumerate() {
+            let key = u64::from_le_bytes(entry[0..8].try_into().unwrap());
+            let mv = u16::from_le_bytes(entry[8..10].try_into().unwrap());
+            let weight = u16::from_le_bytes(entry[10..12].try_into().unwrap());
+
+            let mv = Move::from_raw(mv).ok_or(BookError::InvalidEntry(idx))?;
+
+            entries.push(BookEntry { key, mv, weight });
+        }
+
+        // lookups rely on the order, don't trust other tools to have kept it
+        entries.sort_by_key(|entry| entry.key);
+
+        Ok(Self { size, entries })
+    }
+
+    pub fn save(&self, path: &str) -> std::io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+
+        out.write_all(&MAGIC)?;
+        out.write_all(&[VERSION, self.size.raw(), 0, 0])?;
+
+        for entry in &self.entries {
+            out.write_all(&entry.key.to_le_bytes())?;
+            out.write_all(&entry.mv.raw().to_le_bytes())?;
+            out.write_all(&entry.weight.to_le_bytes())?;
+        }
+
+        out.flush()
+    }
+
+    #[must_use]
+    fn entries(&self, key: u64) -> &[BookEntry] {
+        let start = self.entries.partition_point(|entry| entry.key < key);
+        let end = start + self.entries[start..].partition_point(|entry| entry.key == key);
+
+        &self.entries[start..end]
+    }
+
+    // a random book move, weighted, if the position is in the book
+    #[must_use]
+    pub fn probe(&self, pos: &Position, rng: &mut Sfc64) -> Option<Move> {
+        if pos.size() != self.size {
+            return None;
+        }
+
+        // guard against key collisions and reserves that differ from the book's games
+        let candidates: Vec<_> = self
+            .entries(pos.key())
+            .iter()
+            .filter(|entry| entry.weight > 0 && pos.is_legal(entry.mv))
+            .collect();
+
+        let total: u32 = candidates.iter().map(|entry| entry.weight as u32).sum();
+
+        if total == 0 {
+            return None;
+        }
+
+        let mut choice = rng.next_u32_bounded(total);
+
+        for entry in candidates {
+            if choice < entry.weight as u32 {
+                return Some(entry.mv);
+            }
+
+            choice -= entry.weight as u32;
+        }
+
+        unreachable!()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BookConfig {
+    pub size: BoardSize,
+    pub games: Vec<String>,
+    // in plies from the start of the game
+    pub depth: u16,
+    pub min_games: u32,
+    pub out: String,
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+pub struct BookStats {
+    pub games: usize,
+    pub skipped: usize,
+    pub positions: usize,
+    pub entries: usize,
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+struct MoveStats {
+    games: u32,
+    // two per win and one per draw, from the mover's perspective
+    points: u32,
+}
+
+pub fn build_book(config: &BookConfig) -> Result<BookStats, BookError> {
+    let mut stats = BookStats::default();
+    let mut moves = HashMap::<(u64, u16), MoveStats>::new();
+
+    for path in &config.games {
+        let ptn = std::fs::read_to_string(path).map_err(BookError::Io)?;
+
+        for (idx, game) in split_games(&ptn).into_iter().enumerate() {
+            let game = match game.parse::<PtnGame>() {
+                Ok(game) => game,
+                Err(err) => {
+                    println!("info string Skipping game {} in {}: {:?}", idx + 1, path, err);
+                    stats.skipped += 1;
+                    continue;
+                }
+            };
+
+            if game.start().size() != config.size {
+                stats.skipped += 1;
+                continue;
+            }
+
+            let winner = game.result().map(|result| result.winner());
+
+            let mut pos = *game.start();
+
+            for &mv in game.moves() {
+                if pos.ply() >= config.depth {
+                    break;
+                }
+
+                let points = match winner {
+                    Some(Some(player)) if player == pos.stm() => 2,
+                    Some(Some(_)) => 0,
+                    // unfinished games count as draws
+                    Some(None) | None => 1,
+                };
+
+                let move_stats = moves.entry((pos.key(), mv.raw())).or_default();
+
+                move_stats.games += 1;
+                move_stats.points += points;
+
+                pos = pos.apply_move(mv);
+            }
+
+            stats.games += 1;
+        }
+    }
+
+    let mut entries: Vec<_> = moves
+        .into_iter()
+        .filter(|(_, move_stats)| move_stats.games >= config.min_games && move_stats.points > 0)
+        .map(|((key, mv), move_stats)| BookEntry {
+            key,
+            mv: Move::from_raw(mv).unwrap(),
+            weight: move_stats.points.min(u16::MAX as u32) as u16,
+        })
+        .collect();
+
+    // stable output for the same input, heaviest moves first
+    entries.sort_by_key(|entry| (entry.key, std::cmp::Reverse(entry.weight), entry.mv.raw()));
+
+    stats.entries = entries.len();
+    stats.positions = entries.chunk_by(|a, b| a.key == b.key).count();
+
+    let book = Book {
+        size: config.size,
+        entries,
+    };
+
+    book.save(&config.out).map_err(BookError::Io)?;
+
+    Ok(stats)
+}
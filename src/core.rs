@@ -205,7 +205,12 @@ impl Direction {
 
     #[must_use]
     pub const fn offset(self) -> i8 {
-        [6, -6, -1, 1][self.idx()]
+        [8, -8, -1, 1][self.idx()]
+    }
+
+    #[must_use]
+    pub const fn flip(self) -> Self {
+        Self::from_raw(self as u8 ^ 0x1).unwrap()
     }
 }
 
@@ -224,16 +229,21 @@ impl Display for Direction {
 #[repr(u8)]
 #[rustfmt::skip]
 pub enum Square {
-    A1, B1, C1, D1, E1, F1,
-    A2, B2, C2, D2, E2, F2,
-    A3, B3, C3, D3, E3, F3,
-    A4, B4, C4, D4, E4, F4,
-    A5, B5, C5, D5, E5, F5,
-    A6, B6, C6, D6, E6, F6,
+    A1, B1, C1, D1, E1, F1, G1, H1,
+    A2, B2, C2, D2, E2, F2, G2, H2,
+    A3, B3, C3, D3, E3, F3, G3, H3,
+    A4, B4, C4, D4, E4, F4, G4, H4,
+    A5, B5, C5, D5, E5, F5, G5, H5,
+    A6, B6, C6, D6, E6, F6, G6, H6,
+    A7, B7, C7, D7, E7, F7, G7, H7,
+    A8, B8, C8, D8, E8, F8, G8, H8,
 }
 
+// squares are always laid out as on an 8x8 board, smaller
+// boards just occupy the lower left corner of it
 impl Square {
-    pub const COUNT: usize = 36;
+    pub const COUNT: usize = 64;
+    pub const STRIDE: u32 = 8;
 
     #[must_use]
     pub const fn from_raw(raw: u8) -> Option<Self> {
@@ -247,10 +257,10 @@ impl Square {
 
     #[must_use]
     pub const fn from_file_rank(file: u32, rank: u32) -> Option<Self> {
-        if file >= 6 || rank >= 6 {
+        if file >= Self::STRIDE || rank >= Self::STRIDE {
             None
         } else {
-            Some(Self::from_raw((rank as u8 * 6) + file as u8).unwrap())
+            Some(Self::from_raw((rank * Self::STRIDE + file) as u8).unwrap())
         }
     }
 
@@ -266,12 +276,12 @@ impl Square {
 
     #[must_use]
     pub const fn rank(self) -> u32 {
-        self.raw() as u32 / 6
+        self.raw() as u32 / Self::STRIDE
     }
 
     #[must_use]
     pub const fn file(self) -> u32 {
-        self.raw() as u32 % 6
+        self.raw() as u32 % Self::STRIDE
     }
 
     #[must_use]
@@ -293,7 +303,7 @@ impl Square {
     pub const fn shift_checked(self, dir: Direction) -> Option<Self> {
         match dir {
             Direction::Left if self.file() == 0 => None,
-            Direction::Right if self.file() == 5 => None,
+            Direction::Right if self.file() == Self::STRIDE - 1 => None,
             _ => self.shift(dir),
         }
     }
@@ -334,12 +344,12 @@ impl FromStr for Square {
         }
 
         let file = bytes[0];
-        if !(b'a'..=b'f').contains(&file) {
+        if !(b'a'..=b'h').contains(&file) {
             return Err(SquareStrError::InvalidFile);
         }
 
         let rank = bytes[1];
-        if !(b'1'..=b'6').contains(&rank) {
+        if !(b'1'..=b'8').contains(&rank) {
             return Err(SquareStrError::InvalidRank);
         }
 
@@ -360,3 +370,97 @@ impl Iterator for SquareIterator {
         sq
     }
 }
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct BoardSize {
+    raw: u8,
+}
+
+impl BoardSize {
+    pub const MIN: u8 = 3;
+    pub const MAX: u8 = 8;
+    pub const COUNT: usize = (Self::MAX - Self::MIN + 1) as usize;
+
+    pub const DEFAULT: Self = Self { raw: 6 };
+
+    #[must_use]
+    pub const fn new(raw: u8) -> Option<Self> {
+        if raw >= Self::MIN && raw <= Self::MAX {
+            Some(Self { raw })
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    pub const fn raw(self) -> u8 {
+        self.raw
+    }
+
+    #[must_use]
+    pub const fn idx(self) -> usize {
+        (self.raw - Self::MIN) as usize
+    }
+
+    #[must_use]
+    pub const fn carry_limit(self) -> u8 {
+        self.raw
+    }
+
+    #[must_use]
+    pub const fn default_flats(self) -> u8 {
+        [10, 15, 21, 30, 40, 50][self.idx()]
+    }
+
+    #[must_use]
+    pub const fn default_caps(self) -> u8 {
+        [0, 0, 1, 1, 2, 2][self.idx()]
+    }
+
+    #[must_use]
+    pub const fn board(self) -> Bitboard {
+        let rank = (1 << self.raw) - 1;
+
+        let mut raw = 0;
+        let mut i = 0;
+
+        while i < self.raw {
+            raw |= rank << (i as u32 * Square::STRIDE);
+            i += 1;
+        }
+
+        Bitboard::from_raw(raw)
+    }
+
+    #[must_use]
+    pub const fn edge(self, dir: Direction) -> Bitboard {
+        let last = self.raw as u32 - 1;
+        match dir {
+            Direction::Up => Bitboard::LOWER_EDGE.shl(last * Square::STRIDE).and(self.board()),
+            Direction::Down => Bitboard::LOWER_EDGE.and(self.board()),
+            Direction::Left => Bitboard::LEFT_EDGE.and(self.board()),
+            Direction::Right => Bitboard::LEFT_EDGE.shl(last).and(self.board()),
+        }
+    }
+
+    #[must_use]
+    pub const fn contains(self, sq: Square) -> bool {
+        sq.file() < self.raw as u32 && sq.rank() < self.raw as u32
+    }
+
+    pub fn squares(self) -> impl Iterator<Item = Square> {
+        self.board().into_iter()
+    }
+}
+
+impl Default for BoardSize {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl Display for BoardSize {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.raw)
+    }
+}
@@ -0,0 +1,230 @@
+/*
+ * syntaks, a TEI Tak engine
+ * Copyright (c) 2026 Ciekce
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::board::{GameConfig, Position};
+use crate::core::Player;
+use crate::limit::Limits;
+use crate::ptn::GameResult;
+use crate::search::{MAX_DEPTH, Score, Searcher, is_decisive};
+use crate::selfplay::random_opening;
+use crate::util::rng::Sfc64;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::str::FromStr;
+use std::time::Instant;
+
+pub const DEFAULT_DATAGEN_NODES: usize = 5000;
+pub const DEFAULT_DATAGEN_OPENING_PLIES: usize = 8;
+
+// text: one position per line, "<tps> | <score> | <result> | <size> <flats> <caps> <half komi>"
+// binary, little endian:
+//   u8 size, u8 flats, u8 caps, u8 half komi, u8 stm, u16 ply,
+//   i16 score, u8 result (0 = p2 win, 1 = draw, 2 = p1 win),
+//   ceil(size * size / 8) bytes of occupancy, one bit per square in the order a1, b1, ..., a2, ...,
+//   then for each occupied square: u8 top piece type | min(height, 63) << 2, another u8 with the
+//   full height if that was 63, and ceil(height / 8) bytes of stack players, bottom up, set bits are p2
+// in both formats, scores and results are from p1's perspective
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum DataFormat {
+    Text,
+    Binary,
+}
+
+impl FromStr for DataFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Self::Text),
+            "binary" => Ok(Self::Binary),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct DatagenConfig {
+    pub game: GameConfig,
+    pub games: usize,
+    pub nodes: usize,
+    pub opening_plies: usize,
+    pub max_plies: usize,
+    pub seed: u64,
+    pub hash_mib: usize,
+    pub format: DataFormat,
+    pub out: String,
+}
+
+// p1-relative, 0 for a p2 win to 2 for a p1 win
+#[must_use]
+fn result_value(winner: Option<Player>) -> u8 {
+    match winner {
+        Some(Player::P1) => 2,
+        Some(Player::P2) => 0,
+        None => 1,
+    }
+}
+
+fn write_text(out: &mut impl Write, pos: &Position, score: Score, result: u8) -> std::io::Result<()> {
+    let result = match result {
+        2 => "1.0",
+        1 => "0.5",
+        _ => "0.0",
+    };
+
+    let config = pos.config();
+
+    writeln!(
+        out,
+        "{} | {} | {} | {} {} {} {}",
+        pos.tps(),
+        score,
+        result,
+        config.size.raw(),
+        config.flats,
+        config.caps,
+        config.half_komi
+    )
+}
+
+fn write_binary(out: &mut impl Write, pos: &Position, score: Score, result: u8) -> std::io::Result<()> {
+    let config = pos.config();
+
+    out.write_all(&[
+        config.size.raw(),
+        config.flats,
+        config.caps,
+        config.half_komi,
+        pos.stm().raw(),
+    ])?;
+    out.write_all(&pos.ply().to_le_bytes())?;
+    out.write_all(&(score.clamp(i16::MIN as Score, i16::MAX as Score) as i16).to_le_bytes())?;
+    out.write_all(&[result])?;
+
+    let stacks = pos.stacks();
+
+    let mut occupancy = 0u64;
+    for (idx, sq) in pos.size().squares().enumerate() {
+        if !stacks.is_empty(sq) {
+            occupancy |= 1 << idx;
+        }
+    }
+
+    let square_count = pos.size().raw() as usize * pos.size().raw() as usize;
+    out.write_all(&occupancy.to_le_bytes()[..square_count.div_ceil(8)])?;
+
+    for sq in pos.size().squares() {
+        if let Some(top) = stacks.top(sq) {
+            let height = stacks.height(sq);
+
+            out.write_all(&[top.raw() | (height.min(63) << 2)])?;
+            if height >= 63 {
+                out.write_all(&[height])?;
+            }
+
+            out.write_all(&stacks.players(sq).to_le_bytes()[..(height as usize).div_ceil(8)])?;
+        }
+    }
+
+    Ok(())
+}
+
+// positions, with p1-relative scores, and the p1-relative result
+fn play_game(config: &DatagenConfig, searcher: &mut Searcher, rng: &mut Sfc64) -> (Vec<(Position, Score)>, u8) {
+    let mut pos = Position::startpos(config.game);
+    let mut key_history = Vec::with_capacity(config.max_plies);
+
+    for mv in random_opening(config.game, config.opening_plies, rng) {
+        key_history.push(pos.key());
+        pos = pos.apply_move(mv);
+    }
+
+    searcher.reset();
+
+    let mut positions = Vec::with_capacity(config.max_plies);
+
+    loop {
+        if let Some(result) = GameResult::from_position(&pos) {
+            return (positions, result_value(result.winner()));
+        }
+
+        let repetitions = key_history.iter().filter(|&&key| key == pos.key()).count();
+        if repetitions >= 2 || key_history.len() >= config.max_plies {
+            return (positions, result_value(None));
+        }
+
+        let mut limits = Limits::new(Instant::now());
+        limits.set_nodes(config.nodes);
+
+        let result = searcher.search(&pos, &key_history, limits, MAX_DEPTH).unwrap();
+
+        // decisive scores say nothing useful about the eval
+        if !is_decisive(result.score) {
+            positions.push((pos, result.score * pos.stm().sign()));
+        }
+
+        key_history.push(pos.key());
+        pos = pos.apply_move(result.best_move);
+    }
+}
+
+// returns the number of positions written
+pub fn run_datagen(config: &DatagenConfig) -> std::io::Result<usize> {
+    let mut out = BufWriter::new(File::create(&config.out)?);
+
+    let mut searcher = Searcher::new();
+    if let Err(err) = searcher.set_tt_size(config.hash_mib) {
+        println!("info string Error: {}, using the default size", err);
+    }
+
+    let mut rng = Sfc64::new(config.seed);
+
+    let start_time = Instant::now();
+    let mut total = 0;
+
+    for game in 0..config.games {
+        let (positions, result) = play_game(config, &mut searcher, &mut rng);
+
+        for (pos, score) in &positions {
+            match config.format {
+                DataFormat::Text => write_text(&mut out, pos, *score, result)?,
+                DataFormat::Binary => write_binary(&mut out, pos, *score, result)?,
+            }
+        }
+
+        total += positions.len();
+
+        let time = start_time.elapsed().as_secs_f64();
+        println!(
+            "Finished game {}: {} positions, {} total, {:.0} positions/s",
+            game + 1,
+            positions.len(),
+            total,
+            total as f64 / time
+        );
+    }
+
+    out.flush()?;
+
+    Ok(total)
+}
@@ -21,23 +21,54 @@
  * SOFTWARE.
  */
 
-use std::array;
-
 use crate::bitboard::Bitboard;
 use crate::board::Position;
-use crate::core::{Direction, Piece, PieceType, Player, Square};
+use crate::core::{BoardSize, Direction, PieceType, Player, Square};
 use crate::search::Score;
+use std::fmt::{Display, Formatter};
+use std::ops::{Index, IndexMut};
+use std::str::FromStr;
+
+const MAX_RINGS: usize = 7;
 
 #[static_init::dynamic]
-static RINGS: [Bitboard; 5] = {
-    let mut covered = Bitboard::from_raw(1 << 14 | 1 << 15 | 1 << 20 | 1 << 21);
-    let mut curr = covered;
-    array::from_fn(|_| {
-        let r = curr;
-        curr = (curr << 6 | curr >> 6 | curr << 1 | curr >> 1) & !covered;
-        covered |= curr;
-        r
-    })
+static RINGS: [[Bitboard; MAX_RINGS]; BoardSize::COUNT] = {
+    let mut result = [[Bitboard::empty(); MAX_RINGS]; BoardSize::COUNT];
+
+    for (size_idx, rings) in result.iter_mut().enumerate() {
+        let size = BoardSize::new(BoardSize::MIN + size_idx as u8).unwrap();
+        let board = size.board();
+
+        let low = (size.raw() as u32 - 1) / 2;
+        let high = size.raw() as u32 / 2;
+
+        let mut covered = Bitboard::empty();
+        for rank in low..=high {
+            for file in low..=high {
+                covered.set_sq(Square::from_file_rank(file, rank).unwrap());
+            }
+        }
+
+        let mut curr = covered;
+        let mut inner_to_outer = Vec::with_capacity(MAX_RINGS);
+
+        while !curr.is_empty() {
+            inner_to_outer.push(curr);
+            curr = (curr.shift(Direction::Up)
+                | curr.shift(Direction::Down)
+                | curr.shift(Direction::Left)
+                | curr.shift(Direction::Right))
+                & board
+                & !covered;
+            covered |= curr;
+        }
+
+        for (ring, &bb) in rings.iter_mut().zip(inner_to_outer.iter().rev()) {
+            *ring = bb;
+        }
+    }
+
+    result
 };
 
 const ADJACENT_MASKS: [Bitboard; Square::COUNT] = {
@@ -59,7 +90,31 @@ const ADJACENT_MASKS: [Bitboard; Square::COUNT] = {
 };
 
 #[rustfmt::skip]
-const CAP_PSQT: [Score; Square::COUNT] = [
+const CAP_PSQT_3X3: [Score; 9] = [
+     -5,   5,  -5,
+      5,  20,   5,
+     -5,   5,  -5,
+];
+
+#[rustfmt::skip]
+const CAP_PSQT_4X4: [Score; 16] = [
+    -20,  -5,  -5, -20,
+     -5,  20,  20,  -5,
+     -5,  20,  20,  -5,
+    -20,  -5,  -5, -20,
+];
+
+#[rustfmt::skip]
+const CAP_PSQT_5X5: [Score; 25] = [
+    -20,  -5,  -5,  -5, -20,
+     -5,  10,  18,  10,  -5,
+     -5,  18,  35,  18,  -5,
+     -5,  10,  18,  10,  -5,
+    -20,  -5,  -5,  -5, -20,
+];
+
+#[rustfmt::skip]
+const CAP_PSQT_6X6: [Score; 36] = [
     -20,  -5,  -5,  -5,  -5, -20,
      -5,  10,  18,  18,  10,  -5,
      -5,  18,  35,  35,  18,  -5,
@@ -68,14 +123,439 @@ const CAP_PSQT: [Score; Square::COUNT] = [
     -20,  -5,  -5,  -5,  -5, -20,
 ];
 
+#[rustfmt::skip]
+const CAP_PSQT_7X7: [Score; 49] = [
+    -20,  -5,  -5,  -5,  -5,  -5, -20,
+     -5,  10,  18,  18,  18,  10,  -5,
+     -5,  18,  30,  35,  30,  18,  -5,
+     -5,  18,  35,  35,  35,  18,  -5,
+     -5,  18,  30,  35,  30,  18,  -5,
+     -5,  10,  18,  18,  18,  10,  -5,
+    -20,  -5,  -5,  -5,  -5,  -5, -20,
+];
+
+#[rustfmt::skip]
+const CAP_PSQT_8X8: [Score; 64] = [
+    -20,  -5,  -5,  -5,  -5,  -5,  -5, -20,
+     -5,  10,  18,  18,  18,  18,  10,  -5,
+     -5,  18,  30,  35,  35,  30,  18,  -5,
+     -5,  18,  35,  35,  35,  35,  18,  -5,
+     -5,  18,  35,  35,  35,  35,  18,  -5,
+     -5,  18,  30,  35,  35,  30,  18,  -5,
+     -5,  10,  18,  18,  18,  18,  10,  -5,
+    -20,  -5,  -5,  -5,  -5,  -5,  -5, -20,
+];
+
+const CAP_PSQT: [&[Score]; BoardSize::COUNT] = [
+    &CAP_PSQT_3X3,
+    &CAP_PSQT_4X4,
+    &CAP_PSQT_5X5,
+    &CAP_PSQT_6X6,
+    &CAP_PSQT_7X7,
+    &CAP_PSQT_8X8,
+];
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Param {
+    Flat,
+    FlatInHand,
+    Adjacent,
+    Line,
+    Support(PieceType),
+    Captive(PieceType),
+    IsolatedCap,
+    // outermost ring first, inner rings past the end share the last value
+    Ring(usize),
+    Tempo,
+    // empty squares that would complete a road, at most two count
+    RoadThreat,
+    // indexed by rank * size + file
+    CapPsqt(BoardSize, usize),
+}
+
+impl Param {
+    const SUPPORT_OFFSET: usize = 4;
+    const CAPTIVE_OFFSET: usize = Self::SUPPORT_OFFSET + PieceType::COUNT;
+    const ISOLATED_CAP_OFFSET: usize = Self::CAPTIVE_OFFSET + PieceType::COUNT;
+    const RING_OFFSET: usize = Self::ISOLATED_CAP_OFFSET + 1;
+    const TEMPO_OFFSET: usize = Self::RING_OFFSET + MAX_RINGS;
+    const ROAD_THREAT_OFFSET: usize = Self::TEMPO_OFFSET + 1;
+    const CAP_PSQT_OFFSET: usize = Self::ROAD_THREAT_OFFSET + 1;
+
+    // includes the unused psqt entries past the end of smaller boards
+    pub const COUNT: usize = Self::CAP_PSQT_OFFSET + BoardSize::COUNT * Square::COUNT;
+
+    #[must_use]
+    pub const fn idx(self) -> usize {
+        match self {
+            Self::Flat => 0,
+            Self::FlatInHand => 1,
+            Self::Adjacent => 2,
+            Self::Line => 3,
+            Self::Support(pt) => Self::SUPPORT_OFFSET + pt.idx(),
+            Self::Captive(pt) => Self::CAPTIVE_OFFSET + pt.idx(),
+            Self::IsolatedCap => Self::ISOLATED_CAP_OFFSET,
+            Self::Ring(ring) => Self::RING_OFFSET + ring,
+            Self::Tempo => Self::TEMPO_OFFSET,
+            Self::RoadThreat => Self::ROAD_THREAT_OFFSET,
+            Self::CapPsqt(size, idx) => Self::CAP_PSQT_OFFSET + size.idx() * Square::COUNT + idx,
+        }
+    }
+
+    #[must_use]
+    pub fn from_idx(idx: usize) -> Option<Self> {
+        match idx {
+            0 => Some(Self::Flat),
+            1 => Some(Self::FlatInHand),
+            2 => Some(Self::Adjacent),
+            3 => Some(Self::Line),
+            Self::ISOLATED_CAP_OFFSET => Some(Self::IsolatedCap),
+            Self::TEMPO_OFFSET => Some(Self::Tempo),
+            Self::ROAD_THREAT_OFFSET => Some(Self::RoadThreat),
+            _ if idx < Self::CAPTIVE_OFFSET => {
+                PieceType::from_raw((idx - Self::SUPPORT_OFFSET) as u8).map(Self::Support)
+            }
+            _ if idx < Self::ISOLATED_CAP_OFFSET => {
+                PieceType::from_raw((idx - Self::CAPTIVE_OFFSET) as u8).map(Self::Captive)
+            }
+            _ if idx < Self::TEMPO_OFFSET => Some(Self::Ring(idx - Self::RING_OFFSET)),
+            _ if idx < Self::COUNT => {
+                let size = BoardSize::new(BoardSize::MIN + ((idx - Self::CAP_PSQT_OFFSET) / Square::COUNT) as u8)?;
+                let sq_idx = (idx - Self::CAP_PSQT_OFFSET) % Square::COUNT;
+                (sq_idx < size.raw() as usize * size.raw() as usize).then_some(Self::CapPsqt(size, sq_idx))
+            }
+            _ => None,
+        }
+    }
+
+    // every parameter that is actually used
+    pub fn all() -> impl Iterator<Item = Self> {
+        (0..Self::COUNT).filter_map(Self::from_idx)
+    }
+}
+
+impl Display for Param {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let piece_name = |pt: PieceType| match pt {
+            PieceType::Flat => "flat",
+            PieceType::Wall => "wall",
+            PieceType::Capstone => "cap",
+        };
+
+        match self {
+            Self::Flat => write!(f, "flat"),
+            Self::FlatInHand => write!(f, "flat_in_hand"),
+            Self::Adjacent => write!(f, "adjacent"),
+            Self::Line => write!(f, "line"),
+            Self::Support(pt) => write!(f, "support_{}", piece_name(*pt)),
+            Self::Captive(pt) => write!(f, "captive_{}", piece_name(*pt)),
+            Self::IsolatedCap => write!(f, "isolated_cap"),
+            Self::Ring(ring) => write!(f, "ring_{}", ring),
+            Self::Tempo => write!(f, "tempo"),
+            Self::RoadThreat => write!(f, "road_threat"),
+            Self::CapPsqt(size, idx) => {
+                let file = *idx as u32 % size.raw() as u32;
+                let rank = *idx as u32 / size.raw() as u32;
+                write!(
+                    f,
+                    "cap_psqt_{0}x{0}_{1}",
+                    size,
+                    Square::from_file_rank(file, rank).unwrap()
+                )
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EvalParams {
+    values: [Score; Param::COUNT],
+}
+
+pub const DEFAULT_EVAL_PARAMS: EvalParams = {
+    let mut values = [0; Param::COUNT];
+
+    values[Param::Flat.idx()] = 75;
+    values[Param::FlatInHand.idx()] = -13;
+    values[Param::Adjacent.idx()] = 9;
+    values[Param::Line.idx()] = 7;
+
+    values[Param::Support(PieceType::Flat).idx()] = 30;
+    values[Param::Support(PieceType::Wall).idx()] = 35;
+    values[Param::Support(PieceType::Capstone).idx()] = 40;
+
+    values[Param::Captive(PieceType::Flat).idx()] = -40;
+    values[Param::Captive(PieceType::Wall).idx()] = -15;
+    values[Param::Captive(PieceType::Capstone).idx()] = -20;
+
+    values[Param::IsolatedCap.idx()] = -50;
+
+    let ring_values: [Score; MAX_RINGS] = [-40, -15, -5, 8, 2, 2, 2];
+
+    let mut ring = 0;
+    while ring < MAX_RINGS {
+        values[Param::Ring(ring).idx()] = ring_values[ring];
+        ring += 1;
+    }
+
+    values[Param::Tempo.idx()] = 30;
+    values[Param::RoadThreat.idx()] = 60;
+
+    let mut size_idx = 0;
+    while size_idx < BoardSize::COUNT {
+        let size = BoardSize::new(BoardSize::MIN + size_idx as u8).unwrap();
+
+        let mut idx = 0;
+        while idx < CAP_PSQT[size_idx].len() {
+            values[Param::CapPsqt(size, idx).idx()] = CAP_PSQT[size_idx][idx];
+            idx += 1;
+        }
+
+        size_idx += 1;
+    }
+
+    EvalParams { values }
+};
+
+impl EvalParams {
+    #[must_use]
+    pub const fn from_values(values: [Score; Param::COUNT]) -> Self {
+        Self { values }
+    }
+
+    #[must_use]
+    pub fn values(&self) -> &[Score; Param::COUNT] {
+        &self.values
+    }
+}
+
+impl Default for EvalParams {
+    fn default() -> Self {
+        DEFAULT_EVAL_PARAMS
+    }
+}
+
+impl Index<Param> for EvalParams {
+    type Output = Score;
+
+    fn index(&self, param: Param) -> &Self::Output {
+        &self.values[param.idx()]
+    }
+}
+
+impl IndexMut<Param> for EvalParams {
+    fn index_mut(&mut self, param: Param) -> &mut Self::Output {
+        &mut self.values[param.idx()]
+    }
+}
+
+// one "name value" pair per line
+impl Display for EvalParams {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for param in Param::all() {
+            writeln!(f, "{} {}", param, self[param])?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum EvalParamsError {
+    // the line of the offending parameter
+    InvalidLine(usize),
+    UnknownParam(usize),
+    InvalidValue(usize),
+}
+
+// parameters not mentioned keep their default values
+impl FromStr for EvalParams {
+    type Err = EvalParamsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut params = Self::default();
+
+        for (line_idx, line) in s.lines().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            let (name, value) = line
+                .split_once(char::is_whitespace)
+                .ok_or(EvalParamsError::InvalidLine(line_idx + 1))?;
+
+            let param = Param::all()
+                .find(|param| param.to_string() == name)
+                .ok_or(EvalParamsError::UnknownParam(line_idx + 1))?;
+
+            params[param] = value
+                .trim()
+                .parse()
+                .map_err(|_| EvalParamsError::InvalidValue(line_idx + 1))?;
+        }
+
+        Ok(params)
+    }
+}
+
+pub trait EvalAccumulator {
+    fn add(&mut self, param: Param, count: i32);
+    // in halves of the parameter's value, rounded towards zero
+    fn add_halves(&mut self, param: Param, half_count: i32);
+    // which player the following terms belong to, none for the ones that compare both
+    fn set_player(&mut self, _player: Option<Player>) {}
+}
+
+struct ScoreAccumulator<'a> {
+    params: &'a EvalParams,
+    score: Score,
+}
+
+impl EvalAccumulator for ScoreAccumulator<'_> {
+    #[inline(always)]
+    fn add(&mut self, param: Param, count: i32) {
+        self.score += self.params[param] * count;
+    }
+
+    #[inline(always)]
+    fn add_halves(&mut self, param: Param, half_count: i32) {
+        self.score += self.params[param] * half_count / 2;
+    }
+}
+
+const BREAKDOWN_TERMS: [&str; 15] = [
+    "flats + komi",
+    "flats in hand",
+    "adjacency",
+    "lines",
+    "road threats",
+    "support (flat)",
+    "support (wall)",
+    "support (cap)",
+    "captive (flat)",
+    "captive (wall)",
+    "captive (cap)",
+    "cap psqt",
+    "isolated caps",
+    "rings",
+    "tempo",
+];
+
+// rings and tempo look at both players at once, so there is nothing to split
+const FIRST_SHARED_TERM: usize = 13;
+
 #[must_use]
-fn static_eval_player(pos: &Position, player: Player, komi: u32) -> Score {
+fn breakdown_term(param: Param) -> usize {
+    match param {
+        Param::Flat => 0,
+        Param::FlatInHand => 1,
+        Param::Adjacent => 2,
+        Param::Line => 3,
+        Param::RoadThreat => 4,
+        Param::Support(pt) => 5 + pt.idx(),
+        Param::Captive(pt) => 8 + pt.idx(),
+        Param::CapPsqt(_, _) => 11,
+        Param::IsolatedCap => 12,
+        Param::Ring(_) => 13,
+        Param::Tempo => 14,
+    }
+}
+
+// static eval split up by term, as printed by the eval command
+#[derive(Clone, Debug)]
+pub struct EvalBreakdown {
+    stm: Player,
+    // side to move relative, indexed by player with the shared terms last
+    values: [[Score; Player::COUNT + 1]; BREAKDOWN_TERMS.len()],
+}
+
+impl EvalBreakdown {
+    #[must_use]
+    pub fn total(&self) -> Score {
+        self.values.iter().flatten().sum()
+    }
+}
+
+impl Display for EvalBreakdown {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let pawns = |score: Score| format!("{:+.2}", score as f64 / 100.0);
+
+        // each player's terms from their own perspective, totals p1-relative
+        let stm_sign = self.stm.sign();
+
+        writeln!(f, "{:<16} {:>8} {:>8} {:>8}", "term", "p1", "p2", "total")?;
+
+        for (term, (name, values)) in BREAKDOWN_TERMS.iter().zip(self.values.iter()).enumerate() {
+            let p1 = values[Player::P1.idx()] * stm_sign;
+            let p2 = -values[Player::P2.idx()] * stm_sign;
+
+            if term >= FIRST_SHARED_TERM {
+                let shared = values[Player::COUNT] * stm_sign;
+                writeln!(f, "{:<16} {:>8} {:>8} {:>8}", name, "-", "-", pawns(shared))?;
+            } else {
+                writeln!(
+                    f,
+                    "{:<16} {:>8} {:>8} {:>8}",
+                    name,
+                    pawns(p1),
+                    pawns(p2),
+                    pawns(p1 - p2)
+                )?;
+            }
+        }
+
+        write!(
+            f,
+            "{:<16} {:>8} {:>8} {:>8}",
+            "total",
+            "",
+            "",
+            pawns(self.total() * stm_sign)
+        )
+    }
+}
+
+// shares the fast path's arithmetic, so rounding comes out the same
+struct BreakdownAccumulator<'a> {
+    inner: ScoreAccumulator<'a>,
+    player: Option<Player>,
+    breakdown: EvalBreakdown,
+}
+
+impl BreakdownAccumulator<'_> {
+    fn record(&mut self, param: Param, before: Score) {
+        let column = self.player.map_or(Player::COUNT, Player::idx);
+        self.breakdown.values[breakdown_term(param)][column] += self.inner.score - before;
+    }
+}
+
+impl EvalAccumulator for BreakdownAccumulator<'_> {
+    fn add(&mut self, param: Param, count: i32) {
+        let before = self.inner.score;
+        self.inner.add(param, count);
+        self.record(param, before);
+    }
+
+    fn add_halves(&mut self, param: Param, half_count: i32) {
+        let before = self.inner.score;
+        self.inner.add_halves(param, half_count);
+        self.record(param, before);
+    }
+
+    fn set_player(&mut self, player: Option<Player>) {
+        self.player = player;
+    }
+}
+
+// `sign` is 1 if `player` is the side to move, -1 otherwise
+fn eval_player(pos: &Position, player: Player, half_komi: u8, sign: i32, acc: &mut impl EvalAccumulator) {
     let flat_bb = pos.player_piece_bb(PieceType::Flat.with_player(player));
-    let flats = (flat_bb.popcount() + komi) as Score;
-    let flats = flats * 75;
+    let half_flats = (flat_bb.popcount() * 2 + half_komi as u32) as i32;
 
-    let flats_in_hand = pos.flats_in_hand(player) as Score;
-    let flats_in_hand = flats_in_hand * -13;
+    acc.add_halves(Param::Flat, half_flats * sign);
+    acc.add(Param::FlatInHand, pos.flats_in_hand(player) as i32 * sign);
 
     let road_bb = pos.roads(player);
 
@@ -85,17 +565,20 @@ fn static_eval_player(pos: &Position, player: Player, komi: u32) -> Score {
     let line_horz = adj_horz & adj_horz.shift(Direction::Left);
     let line_vert = adj_vert & adj_vert.shift(Direction::Down);
 
-    let adj_value = (adj_horz.popcount() + adj_vert.popcount()) as Score;
-    let line_value = (line_horz.popcount() + line_vert.popcount()) as Score;
+    acc.add(
+        Param::Adjacent,
+        (adj_horz.popcount() + adj_vert.popcount()) as i32 * sign,
+    );
+    acc.add(Param::Line, (line_horz.popcount() + line_vert.popcount()) as i32 * sign);
 
-    let adj_value = adj_value * 9;
-    let line_value = line_value * 7;
+    let road_threats = pos.placement_threats(player).popcount().min(2);
+    acc.add(Param::RoadThreat, road_threats as i32 * sign);
 
     let stacks = &pos.stacks();
-    let player_flip = if player == Player::P2 { u64::MAX } else { 0 };
+    let player_flip = if player == Player::P2 { u128::MAX } else { 0 };
 
-    let mut support_score = 0;
-    let mut captive_score = 0;
+    // only the pieces that can be picked up in one go, plus the one below them
+    let window = pos.size().carry_limit() + 1;
 
     for sq in pos.player_bb(player) {
         let mut height = stacks.height(sq);
@@ -106,66 +589,81 @@ fn static_eval_player(pos: &Position, player: Player, komi: u32) -> Score {
 
         let mut players = stacks.players(sq) ^ player_flip;
 
-        if height > 7 {
-            players >>= height - 7;
-            height = 7;
+        if height > window {
+            players >>= height - window;
+            height = window;
         }
 
         let mask = (1 << (height - 1)) - 1;
 
-        let support_count = (!players & mask).count_ones() as Score;
-        let captive_count = (players & mask).count_ones() as Score;
+        let support_count = (!players & mask).count_ones() as i32;
+        let captive_count = (players & mask).count_ones() as i32;
 
-        match stacks.top(sq).unwrap() {
-            PieceType::Flat => {
-                support_score += support_count * 30;
-                captive_score += captive_count * -40;
-            }
-            PieceType::Wall => {
-                support_score += support_count * 35;
-                captive_score += captive_count * -15;
-            }
-            PieceType::Capstone => {
-                support_score += support_count * 40;
-                captive_score += captive_count * -20;
-            }
-        }
+        let top = stacks.top(sq).unwrap();
+
+        acc.add(Param::Support(top), support_count * sign);
+        acc.add(Param::Captive(top), captive_count * sign);
     }
 
     let isolated_mask = pos.occ() & !flat_bb;
 
-    let mut psqt_score = 0;
-    let mut isolated_cap_score = 0;
-
     for cap_sq in pos.player_piece_bb(PieceType::Capstone.with_player(player)) {
-        psqt_score += CAP_PSQT[cap_sq.idx()];
+        let size = pos.size();
+        let psqt_idx = (cap_sq.rank() * size.raw() as u32 + cap_sq.file()) as usize;
+
+        acc.add(Param::CapPsqt(size, psqt_idx), sign);
 
         let adjacent = ADJACENT_MASKS[cap_sq.idx()];
         if (adjacent & isolated_mask).is_empty() {
-            isolated_cap_score -= 50;
+            acc.add(Param::IsolatedCap, sign);
         }
     }
+}
 
-    flats + flats_in_hand + adj_value + line_value + support_score + captive_score + psqt_score + isolated_cap_score
+// from the side to move's perspective
+pub fn evaluate(pos: &Position, acc: &mut impl EvalAccumulator) {
+    let sign = |player: Player| if player == pos.stm() { 1 } else { -1 };
+
+    acc.set_player(Some(Player::P1));
+    eval_player(pos, Player::P1, 0, sign(Player::P1), acc);
+
+    acc.set_player(Some(Player::P2));
+    eval_player(pos, Player::P2, pos.half_komi(), sign(Player::P2), acc);
+
+    acc.set_player(None);
+
+    let p1_flat_bb = pos.player_piece_bb(PieceType::Flat.with_player(Player::P1));
+    let p2_flat_bb = pos.player_piece_bb(PieceType::Flat.with_player(Player::P2));
+
+    for (ring, &ring_bb) in RINGS[pos.size().idx()].iter().enumerate() {
+        let diff = (p1_flat_bb & ring_bb).popcount() as i32 - (p2_flat_bb & ring_bb).popcount() as i32;
+        acc.add(Param::Ring(ring), diff * sign(Player::P1));
+    }
+
+    acc.add(Param::Tempo, 1);
 }
 
 #[must_use]
-pub fn static_eval(pos: &Position) -> Score {
-    let p1_score = static_eval_player(pos, Player::P1, 0);
-    let p2_score = static_eval_player(pos, Player::P2, Position::KOMI);
+pub fn static_eval(pos: &Position, params: &EvalParams) -> Score {
+    let mut acc = ScoreAccumulator { params, score: 0 };
+    evaluate(pos, &mut acc);
+    acc.score
+}
 
-    let p1_flat_bb = pos.player_piece_bb(Piece::P1Flat);
-    let p2_flat_bb = pos.player_piece_bb(Piece::P2Flat);
+#[must_use]
+pub fn eval_breakdown(pos: &Position, params: &EvalParams) -> EvalBreakdown {
+    let mut acc = BreakdownAccumulator {
+        inner: ScoreAccumulator { params, score: 0 },
+        player: None,
+        breakdown: EvalBreakdown {
+            stm: pos.stm(),
+            values: [[0; Player::COUNT + 1]; BREAKDOWN_TERMS.len()],
+        },
+    };
 
-    let flat_position_quality_diff = RINGS
-        .iter()
-        .zip([2, 8, -5, -15, -40])
-        .map(|(&ring, value)| {
-            (p1_flat_bb & ring).popcount() as i32 * value - (p2_flat_bb & ring).popcount() as i32 * value
-        })
-        .sum::<i32>();
+    evaluate(pos, &mut acc);
 
-    let eval = p1_score - p2_score + flat_position_quality_diff;
+    debug_assert_eq!(acc.breakdown.total(), static_eval(pos, params));
 
-    eval * pos.stm().sign() + 30
+    acc.breakdown
 }
@@ -0,0 +1,406 @@
+/*
+ * syntaks, a TEI Tak engine
+ * Copyright (c) 2026 Ciekce
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+// c abi for embedding the engine, see ffi/syntaks.h for the documented interface.
+// every function checks its pointers for null, strings are nul-terminated utf-8
+
+use crate::board::{GameConfig, MAX_HALF_KOMI, Position};
+use crate::core::BoardSize;
+use crate::limit::Limits;
+use crate::movegen::generate_moves;
+use crate::observer::{SearchCollector, mate_distance};
+use crate::ptn::GameResult;
+use crate::search::{MAX_DEPTH, MAX_THREADS, Searcher};
+use crate::takmove::Move;
+use crate::ttable::MAX_TT_SIZE_MIB;
+use std::ffi::{CStr, c_char};
+use std::sync::Arc;
+use std::time::Instant;
+
+pub const SYNTAKS_OK: i32 = 0;
+pub const SYNTAKS_ERR_NULL: i32 = 1;
+pub const SYNTAKS_ERR_INVALID_ARGUMENT: i32 = 2;
+pub const SYNTAKS_ERR_INVALID_TPS: i32 = 3;
+pub const SYNTAKS_ERR_INVALID_MOVE: i32 = 4;
+pub const SYNTAKS_ERR_ILLEGAL_MOVE: i32 = 5;
+pub const SYNTAKS_ERR_BUFFER_TOO_SMALL: i32 = 6;
+pub const SYNTAKS_ERR_GAME_OVER: i32 = 7;
+pub const SYNTAKS_ERR_NO_LIMITS: i32 = 8;
+pub const SYNTAKS_ERR_ALLOC: i32 = 9;
+
+const MOVE_BUFFER_SIZE: usize = 16;
+const PV_BUFFER_SIZE: usize = 512;
+
+pub struct SyntaksEngine {
+    searcher: Searcher,
+    collector: Arc<SearchCollector>,
+    config: GameConfig,
+    pos: Position,
+    key_history: Vec<u64>,
+}
+
+#[repr(C)]
+pub struct SyntaksLimits {
+    pub depth: i32,
+    pub nodes: u64,
+    pub movetime_ms: u64,
+}
+
+#[repr(C)]
+pub struct SyntaksSearchResult {
+    pub best_move: [c_char; MOVE_BUFFER_SIZE],
+    pub score: i32,
+    pub mate: i32,
+    pub depth: i32,
+    pub nodes: u64,
+    pub pv: [c_char; PV_BUFFER_SIZE],
+}
+
+// copies the string with a nul terminator, or fails without touching the buffer
+#[must_use]
+fn write_str(s: &str, dst: &mut [c_char]) -> i32 {
+    if s.len() >= dst.len() {
+        return SYNTAKS_ERR_BUFFER_TOO_SMALL;
+    }
+
+    for (dst, &byte) in dst.iter_mut().zip(s.as_bytes()) {
+        *dst = byte as c_char;
+    }
+
+    dst[s.len()] = 0;
+
+    SYNTAKS_OK
+}
+
+//SAFETY: ptr must be null or point to a nul-terminated string
+#[must_use]
+unsafe fn read_str<'a>(ptr: *const c_char) -> Option<&'a str> {
+    if ptr.is_null() {
+        return None;
+    }
+
+    unsafe { CStr::from_ptr(ptr) }.to_str().ok()
+}
+
+//SAFETY: buf must be null or valid for writes of len bytes
+#[must_use]
+unsafe fn write_to_buffer(s: &str, buf: *mut c_char, len: usize) -> i32 {
+    if buf.is_null() {
+        return SYNTAKS_ERR_NULL;
+    }
+
+    write_str(s, unsafe { std::slice::from_raw_parts_mut(buf, len) })
+}
+
+impl SyntaksEngine {
+    fn apply_moves(&mut self, moves: &str) -> i32 {
+        for move_str in moves.split_ascii_whitespace() {
+            let Ok(mv) = move_str.parse::<Move>() else {
+                return SYNTAKS_ERR_INVALID_MOVE;
+            };
+
+            if !self.pos.is_legal(mv) {
+                return SYNTAKS_ERR_ILLEGAL_MOVE;
+            }
+
+            self.key_history.push(self.pos.key());
+            self.pos = self.pos.apply_move(mv);
+        }
+
+        SYNTAKS_OK
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn syntaks_engine_new() -> *mut SyntaksEngine {
+    let collector = Arc::new(SearchCollector::new());
+
+    let mut searcher = Searcher::new();
+    searcher.set_observer(Some(collector.clone()));
+
+    let config = GameConfig::default();
+
+    Box::into_raw(Box::new(SyntaksEngine {
+        searcher,
+        collector,
+        config,
+        pos: Position::startpos(config),
+        key_history: Vec::with_capacity(1024),
+    }))
+}
+
+//SAFETY: engine must be null or have come from syntaks_engine_new, and not be used afterwards
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn syntaks_engine_free(engine: *mut SyntaksEngine) {
+    if !engine.is_null() {
+        drop(unsafe { Box::from_raw(engine) });
+    }
+}
+
+//SAFETY: engine must be null or valid
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn syntaks_set_hash(engine: *mut SyntaksEngine, size_mib: usize) -> i32 {
+    let Some(engine) = (unsafe { engine.as_mut() }) else {
+        return SYNTAKS_ERR_NULL;
+    };
+
+    match engine.searcher.set_tt_size(size_mib.clamp(1, MAX_TT_SIZE_MIB)) {
+        Ok(()) => SYNTAKS_OK,
+        Err(_) => SYNTAKS_ERR_ALLOC,
+    }
+}
+
+//SAFETY: engine must be null or valid
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn syntaks_set_threads(engine: *mut SyntaksEngine, threads: u32) -> i32 {
+    let Some(engine) = (unsafe { engine.as_mut() }) else {
+        return SYNTAKS_ERR_NULL;
+    };
+
+    engine.searcher.set_threads(threads.clamp(1, MAX_THREADS));
+
+    SYNTAKS_OK
+}
+
+//SAFETY: engine must be null or valid
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn syntaks_new_game(engine: *mut SyntaksEngine, size: u8, half_komi: u8) -> i32 {
+    let Some(engine) = (unsafe { engine.as_mut() }) else {
+        return SYNTAKS_ERR_NULL;
+    };
+
+    let Some(size) = BoardSize::new(size) else {
+        return SYNTAKS_ERR_INVALID_ARGUMENT;
+    };
+
+    if half_komi > MAX_HALF_KOMI {
+        return SYNTAKS_ERR_INVALID_ARGUMENT;
+    }
+
+    engine.config = GameConfig::new(size);
+    engine.config.half_komi = half_komi;
+
+    engine.pos = Position::startpos(engine.config);
+    engine.key_history.clear();
+
+    engine.searcher.reset();
+
+    SYNTAKS_OK
+}
+
+// the position is left unchanged if any part of it is invalid
+//SAFETY: engine must be null or valid, tps and moves null or nul-terminated strings
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn syntaks_set_position(
+    engine: *mut SyntaksEngine,
+    tps: *const c_char,
+    moves: *const c_char,
+) -> i32 {
+    let Some(engine) = (unsafe { engine.as_mut() }) else {
+        return SYNTAKS_ERR_NULL;
+    };
+
+    let pos = if tps.is_null() {
+        Position::startpos(engine.config)
+    } else {
+        let Some(tps) = (unsafe { read_str(tps) }) else {
+            return SYNTAKS_ERR_INVALID_TPS;
+        };
+
+        let parts: Vec<&str> = tps.split_ascii_whitespace().collect();
+
+        match Position::from_tps_parts(engine.config, &parts) {
+            Ok(pos) => pos,
+            Err(_) => return SYNTAKS_ERR_INVALID_TPS,
+        }
+    };
+
+    let prev_pos = engine.pos;
+    let prev_key_history = std::mem::take(&mut engine.key_history);
+
+    engine.pos = pos;
+
+    if !moves.is_null() {
+        let status = match unsafe { read_str(moves) } {
+            Some(moves) => engine.apply_moves(moves),
+            None => SYNTAKS_ERR_INVALID_MOVE,
+        };
+
+        if status != SYNTAKS_OK {
+            engine.pos = prev_pos;
+            engine.key_history = prev_key_history;
+            return status;
+        }
+    }
+
+    SYNTAKS_OK
+}
+
+//SAFETY: engine must be null or valid, mv null or a nul-terminated string
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn syntaks_apply_move(engine: *mut SyntaksEngine, mv: *const c_char) -> i32 {
+    let Some(engine) = (unsafe { engine.as_mut() }) else {
+        return SYNTAKS_ERR_NULL;
+    };
+
+    let Some(mv) = (unsafe { read_str(mv) }).and_then(|mv| mv.parse::<Move>().ok()) else {
+        return SYNTAKS_ERR_INVALID_MOVE;
+    };
+
+    if !engine.pos.is_legal(mv) {
+        return SYNTAKS_ERR_ILLEGAL_MOVE;
+    }
+
+    engine.key_history.push(engine.pos.key());
+    engine.pos = engine.pos.apply_move(mv);
+
+    SYNTAKS_OK
+}
+
+//SAFETY: engine must be null or valid, buf null or valid for writes of len bytes
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn syntaks_get_tps(engine: *const SyntaksEngine, buf: *mut c_char, len: usize) -> i32 {
+    let Some(engine) = (unsafe { engine.as_ref() }) else {
+        return SYNTAKS_ERR_NULL;
+    };
+
+    unsafe { write_to_buffer(&engine.pos.tps(), buf, len) }
+}
+
+// space-separated
+//SAFETY: engine must be null or valid, buf null or valid for writes of len bytes, count null or valid
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn syntaks_legal_moves(
+    engine: *const SyntaksEngine,
+    buf: *mut c_char,
+    len: usize,
+    count: *mut usize,
+) -> i32 {
+    let Some(engine) = (unsafe { engine.as_ref() }) else {
+        return SYNTAKS_ERR_NULL;
+    };
+
+    let mut moves = Vec::with_capacity(256);
+
+    // no moves once the game is over
+    if GameResult::from_position(&engine.pos).is_none() {
+        generate_moves(&mut moves, &engine.pos);
+    }
+
+    let moves_str = moves.iter().map(Move::to_string).collect::<Vec<_>>().join(" ");
+
+    if let Some(count) = unsafe { count.as_mut() } {
+        *count = moves.len();
+    }
+
+    unsafe { write_to_buffer(&moves_str, buf, len) }
+}
+
+// blocks until the search finishes
+//SAFETY: engine must be null or valid, limits and result null or valid
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn syntaks_search(
+    engine: *mut SyntaksEngine,
+    limits: *const SyntaksLimits,
+    result: *mut SyntaksSearchResult,
+) -> i32 {
+    let Some(engine) = (unsafe { engine.as_mut() }) else {
+        return SYNTAKS_ERR_NULL;
+    };
+
+    let Some(limits) = (unsafe { limits.as_ref() }) else {
+        return SYNTAKS_ERR_NULL;
+    };
+
+    let Some(result) = (unsafe { result.as_mut() }) else {
+        return SYNTAKS_ERR_NULL;
+    };
+
+    if limits.depth <= 0 && limits.nodes == 0 && limits.movetime_ms == 0 {
+        return SYNTAKS_ERR_NO_LIMITS;
+    }
+
+    if GameResult::from_position(&engine.pos).is_some() {
+        return SYNTAKS_ERR_GAME_OVER;
+    }
+
+    let max_depth = if limits.depth > 0 {
+        limits.depth.min(MAX_DEPTH)
+    } else {
+        MAX_DEPTH
+    };
+
+    let mut search_limits = Limits::new(Instant::now());
+
+    if limits.nodes > 0 {
+        search_limits.set_nodes(limits.nodes as usize);
+    }
+
+    if limits.movetime_ms > 0 {
+        search_limits.set_movetime(limits.movetime_ms as f64 / 1000.0);
+    }
+
+    engine.collector.clear();
+
+    let Some(search_result) = engine
+        .searcher
+        .search(&engine.pos, &engine.key_history, search_limits, max_depth)
+    else {
+        return SYNTAKS_ERR_GAME_OVER;
+    };
+
+    let info = engine.collector.last_iteration();
+
+    let pv = info
+        .as_ref()
+        .and_then(|info| info.lines.first())
+        .map_or_else(Vec::new, |line| line.pv.clone());
+
+    // drop moves from the end of the pv until it fits
+    let mut pv_str = String::new();
+    for mv in &pv {
+        let mv = mv.to_string();
+        if pv_str.len() + mv.len() + 1 >= PV_BUFFER_SIZE {
+            break;
+        }
+
+        if !pv_str.is_empty() {
+            pv_str.push(' ');
+        }
+
+        pv_str.push_str(&mv);
+    }
+
+    let status = write_str(&search_result.best_move.to_string(), &mut result.best_move);
+    assert_eq!(status, SYNTAKS_OK);
+
+    let status = write_str(&pv_str, &mut result.pv);
+    assert_eq!(status, SYNTAKS_OK);
+
+    result.score = search_result.score;
+    result.mate = mate_distance(search_result.score).unwrap_or(0);
+    result.depth = search_result.depth;
+    result.nodes = info.map_or(0, |info| info.nodes as u64);
+
+    SYNTAKS_OK
+}
@@ -22,7 +22,7 @@
  */
 
 use crate::bitboard::Bitboard;
-use crate::core::{Direction, Square};
+use crate::core::{BoardSize, Direction, Square};
 
 mod common;
 mod naive;
@@ -36,28 +36,70 @@ mod magic;
 pub type Hit = (u8, Square);
 pub type Hits = [Hit; Direction::COUNT];
 
+// the lookup tables cover the full 8x8 layout, so rays
+// that hit nothing have to be cut off at the edge of smaller boards
+const EDGE_HITS: [[Hits; Square::COUNT]; BoardSize::COUNT] = {
+    let mut result = [[[(0, Square::A1); Direction::COUNT]; Square::COUNT]; BoardSize::COUNT];
+
+    let mut size_idx = 0;
+    while size_idx < BoardSize::COUNT {
+        let size = BoardSize::new(BoardSize::MIN + size_idx as u8).unwrap();
+        let last = size.raw() as u32 - 1;
+
+        let mut sq_idx = 0;
+        while let Some(sq) = Square::from_raw(sq_idx) {
+            if size.contains(sq) {
+                let mut dir_idx = 0;
+                while let Some(dir) = Direction::from_raw(dir_idx) {
+                    let dist = match dir {
+                        Direction::Up => last - sq.rank(),
+                        Direction::Down => sq.rank(),
+                        Direction::Left => sq.file(),
+                        Direction::Right => last - sq.file(),
+                    };
+
+                    let offset = dir.offset() as i32 * dist as i32;
+                    let edge_sq = Square::from_raw((sq.raw() as i32 + offset) as u8).unwrap();
+
+                    result[size_idx][sq.idx()][dir.idx()] = (dist as u8, edge_sq);
+
+                    dir_idx += 1;
+                }
+            }
+
+            sq_idx += 1;
+        }
+
+        size_idx += 1;
+    }
+
+    result
+};
+
 #[must_use]
-pub fn find_hit_for_dir(blockers: Bitboard, start: Square, dir: Direction) -> Hit {
+fn clamp_hit(hit: Hit, edge: Hit) -> Hit {
+    if hit.0 > edge.0 { edge } else { hit }
+}
+
+#[must_use]
+pub fn find_hit_for_dir(blockers: Bitboard, start: Square, dir: Direction, size: BoardSize) -> Hit {
     #[cfg(all(feature = "pext", target_feature = "bmi2"))]
-    {
-        pext::find_hit_for_dir_pext(blockers, start, dir)
-    }
+    let hit = pext::find_hit_for_dir_pext(blockers, start, dir);
 
     #[cfg(not(all(feature = "pext", target_feature = "bmi2")))]
-    {
-        magic::find_hit_for_dir_magic(blockers, start, dir)
-    }
+    let hit = magic::find_hit_for_dir_magic(blockers, start, dir);
+
+    clamp_hit(hit, EDGE_HITS[size.idx()][start.idx()][dir.idx()])
 }
 
 #[must_use]
-pub fn find_hits(blockers: Bitboard, start: Square) -> Hits {
+pub fn find_hits(blockers: Bitboard, start: Square, size: BoardSize) -> Hits {
     #[cfg(all(feature = "pext", target_feature = "bmi2"))]
-    {
-        pext::find_hits_pext(blockers, start)
-    }
+    let hits = pext::find_hits_pext(blockers, start);
 
     #[cfg(not(all(feature = "pext", target_feature = "bmi2")))]
-    {
-        magic::find_hits_magic(blockers, start)
-    }
+    let hits = magic::find_hits_magic(blockers, start);
+
+    let edges = &EDGE_HITS[size.idx()][start.idx()];
+    std::array::from_fn(|idx| clamp_hit(hits[idx], edges[idx]))
 }
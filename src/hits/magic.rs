@@ -28,22 +28,26 @@ use crate::hits::naive::find_hits_naive;
 
 #[rustfmt::skip]
 const MAGICS: [u64; Square::COUNT] = [
-    0x0200204004001181, 0x0101002001108a00, 0x0208081002804803, 0x0402240404000200, 0x0809088808000200, 0x2400402a20000220,
-    0x0308004090000108, 0x0040c210003200c2, 0x1610402001080248, 0x00610201018a8000, 0x80204a0800000414, 0x2008010200211100,
-    0x45002001488c0208, 0x0181101002501800, 0x1610402001080248, 0x1203040202044100, 0x0420410041830181, 0x0201800440020001,
-    0x004c018022401088, 0x008100c010400084, 0x1480410500001060, 0x0020160180100012, 0x0420410041830181, 0x808102010e000001,
-    0x1200104200040800, 0x04040c1102000008, 0x0142008202060401, 0x0241010402000ab0, 0xc304081000400002, 0x2008010200211100,
-    0xc4480004b0001002, 0x4404012a10004040, 0x1008181008018890, 0x000400881c000000, 0x0802894040220001, 0x8491034420002200,
+    0x9080001184204004, 0x00c01008a0004000, 0x0500081100c12000, 0x4700090084203000, 0x9200060068210410, 0x0080020014000980, 0x00801a0001005080, 0x0900018021450002,
+    0x3000800220400480, 0x2000400042201000, 0x2201001020010940, 0x8000801000800800, 0x09010008020c1100, 0x0602001012000884, 0x0441002402000100, 0x0140800244802100,
+    0x9220608000c00090, 0x0820004000300120, 0x005000801481a000, 0x0002020010c12208, 0x0202020030200804, 0x000101001c008208, 0x0006140002900088, 0x820002000242a104,
+    0x0c80024140012000, 0x0210200040015001, 0x30410015002001c0, 0x0010008180180011, 0x2510880100041100, 0x000c008080040e00, 0x8000388400100221, 0xc860800480104300,
+    0x8040018028800044, 0x1081004001003080, 0x2800801000802000, 0x0108001000802780, 0x900002002e002008, 0x02130000a9000400, 0x0004800100800a00, 0x50008000c1800100,
+    0x0420208140028001, 0x0202040045040082, 0x4000430020010050, 0x0204100100290021, 0x8000080001030010, 0x4902000410020048, 0x0000101802040001, 0x02000400408e0001,
+    0x210020c009800180, 0x8401200240008080, 0x84c0852000100080, 0x8800210008500100, 0x004c140801001100, 0x4110180c00010100, 0x0209500802218400, 0xa20441004400a600,
+    0x0080000489022043, 0xc000802051004003, 0x0041002000326843, 0x080240102002001a, 0x4821000800041013, 0x0005000228840009, 0x2002880102509004, 0x9010012401014086,
 ];
 
 #[rustfmt::skip]
 const SHIFTS: [u32; Square::COUNT] = [
-    56, 57, 57, 57, 57, 56,
-    57, 58, 58, 58, 58, 57,
-    57, 58, 58, 58, 58, 57,
-    57, 58, 58, 58, 58, 57,
-    57, 58, 58, 58, 58, 57,
-    56, 57, 57, 57, 57, 56,
+    52, 53, 53, 53, 53, 53, 53, 52,
+    53, 54, 54, 54, 54, 54, 54, 53,
+    53, 54, 54, 54, 54, 54, 54, 53,
+    53, 54, 54, 54, 54, 54, 54, 53,
+    53, 54, 54, 54, 54, 54, 54, 53,
+    53, 54, 54, 54, 54, 54, 54, 53,
+    53, 54, 54, 54, 54, 54, 54, 53,
+    52, 53, 53, 53, 53, 53, 53, 52,
 ];
 
 #[derive(Copy, Clone, Debug)]
@@ -0,0 +1,66 @@
+/*
+ * syntaks, a TEI Tak engine
+ * Copyright (c) 2026 Ciekce
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+// the engine as a library. the modules below are the public api: positions from tps,
+// legality, move application and generation, static eval, and searching with limits.
+// everything else is an implementation detail of those, the tei frontend and the c abi
+
+pub mod bench;
+pub mod bitboard;
+pub mod board;
+mod book;
+pub mod core;
+mod correction;
+mod datagen;
+pub mod eval;
+mod ffi;
+mod history;
+mod hits;
+mod keys;
+pub mod limit;
+pub mod movegen;
+mod movepick;
+pub mod nnue;
+mod node_counter;
+pub mod observer;
+pub mod perft;
+pub mod ptn;
+mod road;
+pub mod search;
+mod selfplay;
+pub mod takmove;
+pub mod tei;
+mod thread;
+mod tinue;
+mod ttable;
+mod tune;
+mod util;
+
+pub use board::{GameConfig, Position, TpsError};
+pub use core::{BoardSize, PieceType, Player, Square};
+pub use eval::{EvalParams, static_eval};
+pub use limit::Limits;
+pub use movegen::generate_moves;
+pub use search::{SearchResult, Searcher};
+pub use takmove::Move;
+pub use ttable::TtAllocError;
@@ -21,6 +21,7 @@
  * SOFTWARE.
  */
 
+use crate::search::{SCORE_MATE, Score, is_win};
 use std::time::Instant;
 
 const TIME_CHECK_INTERVAL: usize = 4096;
@@ -32,8 +33,10 @@ struct TimeManager {
 }
 
 impl TimeManager {
-    fn new(remaining: f64, increment: f64) -> Self {
-        let base_time = remaining / 20.0 + increment;
+    fn new(remaining: f64, increment: f64, moves_to_go: Option<u32>) -> Self {
+        // assume sudden death without a move count
+        let moves_left = moves_to_go.map_or(20.0, |moves_to_go| moves_to_go.max(1) as f64);
+        let base_time = remaining / moves_left + increment;
 
         let max_time = remaining * 0.8;
         let opt_time = (base_time * 0.6).min(max_time);
@@ -57,6 +60,8 @@ pub struct Limits {
     nodes: Option<usize>,
     movetime: Option<f64>,
     time_manager: Option<TimeManager>,
+    // in moves, not plies
+    mate: Option<i32>,
 }
 
 impl Limits {
@@ -66,6 +71,7 @@ impl Limits {
             nodes: None,
             movetime: None,
             time_manager: None,
+            mate: None,
         }
     }
 
@@ -89,25 +95,41 @@ impl Limits {
         }
     }
 
-    pub fn set_time_manager(&mut self, remaining: f64, increment: f64) -> bool {
+    pub fn set_time_manager(&mut self, remaining: f64, increment: f64, moves_to_go: Option<u32>) -> bool {
         match self.time_manager {
             None => {
-                self.time_manager = Some(TimeManager::new(remaining, increment));
+                self.time_manager = Some(TimeManager::new(remaining, increment, moves_to_go));
                 true
             }
             Some(_) => false,
         }
     }
 
+    pub fn set_mate(&mut self, moves: i32) -> bool {
+        match self.mate {
+            None => {
+                self.mate = Some(moves);
+                true
+            }
+            Some(_) => false,
+        }
+    }
+
+    #[must_use]
+    pub fn is_mate_found(&self, score: Score) -> bool {
+        self.mate
+            .is_some_and(|moves| is_win(score) && (SCORE_MATE - score + 1) / 2 <= moves)
+    }
+
     #[must_use]
-    pub fn should_stop_soft(&self, nodes: usize, best_move_nodes_fraction: f64) -> bool {
+    pub fn should_stop_soft(&self, nodes: usize, best_move_nodes_fraction: f64, uncounted_time: f64) -> bool {
         if let Some(max_nodes) = self.nodes
             && nodes >= max_nodes
         {
             return true;
         }
 
-        let time = self.start_time.elapsed().as_secs_f64();
+        let time = self.start_time.elapsed().as_secs_f64() - uncounted_time;
 
         if let Some(movetime) = self.movetime
             && time >= movetime
@@ -125,7 +147,7 @@ impl Limits {
     }
 
     #[must_use]
-    pub fn should_stop_hard(&self, nodes: usize) -> bool {
+    pub fn should_stop_hard(&self, nodes: usize, uncounted_time: f64) -> bool {
         if let Some(max_nodes) = self.nodes
             && nodes >= max_nodes
         {
@@ -136,7 +158,7 @@ impl Limits {
             && nodes.is_multiple_of(TIME_CHECK_INTERVAL)
             && (self.movetime.is_some() || self.time_manager.is_some())
         {
-            let time = self.start_time.elapsed().as_secs_f64();
+            let time = self.start_time.elapsed().as_secs_f64() - uncounted_time;
 
             if let Some(movetime) = self.movetime
                 && time >= movetime
@@ -21,27 +21,26 @@
  * SOFTWARE.
  */
 
-mod bitboard;
-mod board;
-mod core;
-mod correction;
-mod eval;
-mod history;
-mod hits;
-mod keys;
-mod limit;
-mod movegen;
-mod movepick;
-mod node_counter;
-mod perft;
-mod road;
-mod search;
-mod takmove;
-mod tei;
-mod thread;
-mod ttable;
-mod util;
+use syntaks::bench::{DEFAULT_BENCH_DEPTH, run_bench};
 
 fn main() {
-    tei::run();
+    let args: Vec<String> = std::env::args().collect();
+
+    if args.get(1).map(String::as_str) == Some("bench") {
+        let depth = match args.get(2) {
+            Some(depth) => match depth.parse::<i32>() {
+                Ok(depth) if depth > 0 => depth,
+                _ => {
+                    eprintln!("Invalid depth '{}'", depth);
+                    std::process::exit(1);
+                }
+            },
+            None => DEFAULT_BENCH_DEPTH,
+        };
+
+        run_bench(depth);
+        return;
+    }
+
+    syntaks::tei::run();
 }
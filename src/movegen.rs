@@ -27,7 +27,7 @@ use crate::hits::find_hits;
 use crate::takmove::Move;
 
 fn generate_starting_moves(dst: &mut Vec<Move>, pos: &Position) {
-    for sq in !pos.occ() {
+    for sq in pos.empty_squares() {
         dst.push(Move::placement(PieceType::Flat, sq));
     }
 }
@@ -40,7 +40,7 @@ fn generate_placements(dst: &mut Vec<Move>, pos: &Position) {
         return;
     }
 
-    for sq in !pos.occ() {
+    for sq in pos.empty_squares() {
         if caps > 0 {
             dst.push(Move::placement(PieceType::Capstone, sq));
         }
@@ -67,11 +67,11 @@ fn do_spreads(dst: &mut Vec<Move>, sq: Square, dir: Direction, lsb: u16, mut pat
 fn generate_spreads(dst: &mut Vec<Move>, pos: &Position) {
     for sq in pos.player_bb(pos.stm()) {
         let top = pos.stacks().top(sq).unwrap();
-        let max = pos.stacks().height(sq).min(6);
+        let max = pos.stacks().height(sq).min(pos.size().carry_limit());
 
         let start_bit = (1 << Position::CARRY_LIMIT) >> max;
 
-        let hits = find_hits(pos.all_blockers(), sq);
+        let hits = find_hits(pos.all_blockers(), sq, pos.size());
 
         for dir in [Direction::Up, Direction::Down, Direction::Left, Direction::Right] {
             let (mut dist, hit_sq) = hits[dir.idx()];
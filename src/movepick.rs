@@ -77,7 +77,6 @@ impl KillerTable {
 }
 
 pub struct Movepicker<'a> {
-    pos: &'a Position,
     moves: &'a mut Vec<Move>,
     scores: &'a mut Vec<i32>,
     idx: usize,
@@ -89,7 +88,6 @@ pub struct Movepicker<'a> {
 
 impl<'a> Movepicker<'a> {
     pub fn new(
-        pos: &'a Position,
         moves: &'a mut Vec<Move>,
         scores: &'a mut Vec<Score>,
         tt_move: Option<Move>,
@@ -97,7 +95,6 @@ impl<'a> Movepicker<'a> {
         prev_move: Option<Move>,
     ) -> Self {
         Self {
-            pos,
             moves,
             scores,
             idx: 0,
@@ -108,13 +105,24 @@ impl<'a> Movepicker<'a> {
         }
     }
 
-    fn score_moves(&mut self, history: &History) {
+    fn score_moves(&mut self, pos: &Position, history: &History) {
+        const ROAD_BLOCK_BONUS: i32 = 1 << 16;
+
+        // outside the root, search returns before getting here when we can place a road ourselves
+        let road_blocks = pos.placement_threats(pos.stm().flip());
+
         self.scores.clear();
         for mv in self.moves.iter() {
-            let mut score = history.score(self.pos, *mv, self.prev_move);
+            let mut score = history.score(pos, *mv, self.prev_move);
 
             if !mv.is_spread() {
                 score += 100;
+
+                if road_blocks.has_sq(mv.sq()) {
+                    score += ROAD_BLOCK_BONUS;
+                }
+            } else if !(mv.affected_squares().without_sq(mv.sq()) & road_blocks).is_empty() {
+                score += ROAD_BLOCK_BONUS;
             }
 
             // scores have to be less than 2²⁰ to make the movepicking more efficient
@@ -142,12 +150,13 @@ impl<'a> Movepicker<'a> {
         self.moves[self.idx]
     }
 
-    pub fn next(&mut self, history: &History) -> Option<Move> {
+    // `pos` has to be the same position every time
+    pub fn next(&mut self, pos: &Position, history: &History) -> Option<Move> {
         while self.stage != Stage::End {
             match self.stage {
                 Stage::TtMove => {
                     if let Some(tt_move) = self.tt_move
-                        && self.pos.is_legal(tt_move)
+                        && pos.is_legal(tt_move)
                     {
                         self.stage = self.stage.next();
                         return Some(tt_move);
@@ -156,7 +165,7 @@ impl<'a> Movepicker<'a> {
                 Stage::Killer1 => {
                     if let Some(killer) = self.killers.killers[0]
                         && self.tt_move.is_none_or(|tt_move| killer != tt_move)
-                        && self.pos.is_legal(killer)
+                        && pos.is_legal(killer)
                     {
                         self.stage = self.stage.next();
                         return Some(killer);
@@ -165,15 +174,15 @@ impl<'a> Movepicker<'a> {
                 Stage::Killer2 => {
                     if let Some(killer) = self.killers.killers[1]
                         && self.tt_move.is_none_or(|tt_move| killer != tt_move)
-                        && self.pos.is_legal(killer)
+                        && pos.is_legal(killer)
                     {
                         self.stage = self.stage.next();
                         return Some(killer);
                     }
                 }
                 Stage::GenMoves => {
-                    generate_moves(self.moves, self.pos);
-                    self.score_moves(history);
+                    generate_moves(self.moves, pos);
+                    self.score_moves(pos, history);
                 }
                 Stage::Moves => {
                     while self.idx < self.moves.len() {
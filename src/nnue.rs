@@ -0,0 +1,245 @@
+/*
+ * syntaks, a TEI Tak engine
+ * Copyright (c) 2026 Ciekce
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+#[cfg(any(target_feature = "avx2", all(feature = "dispatch", target_arch = "x86_64")))]
+mod avx2;
+
+#[cfg(not(target_feature = "avx2"))]
+mod scalar;
+
+use crate::bitboard::Bitboard;
+use crate::board::Position;
+use crate::core::{Piece, Player, Square};
+use crate::search::Score;
+use std::fmt::{Display, Formatter};
+
+// stack layers below the top piece that get their own features
+pub const STACK_LAYERS: usize = 3;
+
+const FEATURES_PER_SQUARE: usize = Piece::COUNT + STACK_LAYERS * Player::COUNT;
+
+pub const INPUT_SIZE: usize = Square::COUNT * FEATURES_PER_SQUARE;
+pub const HIDDEN_SIZE: usize = 256;
+
+const QA: i32 = 255;
+const QB: i32 = 64;
+const SCALE: i32 = 400;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[repr(C, align(64))]
+struct Align64<T>(T);
+
+// quantised weights, stored in the file in this order as little-endian i16s
+#[repr(C)]
+pub struct Network {
+    feature_weights: [Align64<[i16; HIDDEN_SIZE]>; INPUT_SIZE],
+    feature_bias: Align64<[i16; HIDDEN_SIZE]>,
+    // side to move's half of the accumulator first
+    output_weights: [Align64<[i16; HIDDEN_SIZE]>; Player::COUNT],
+    output_bias: i16,
+}
+
+#[derive(Debug)]
+pub enum NetworkError {
+    Io(std::io::Error),
+    WrongSize(usize),
+}
+
+impl Display for NetworkError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{}", err),
+            Self::WrongSize(size) => write!(f, "wrong file size {} (expected {})", size, Network::FILE_SIZE),
+        }
+    }
+}
+
+impl Network {
+    const PARAM_COUNT: usize = INPUT_SIZE * HIDDEN_SIZE + HIDDEN_SIZE + Player::COUNT * HIDDEN_SIZE + 1;
+    const FILE_SIZE: usize = Self::PARAM_COUNT * size_of::<i16>();
+
+    // trainers commonly pad their output to a multiple of 64 bytes
+    const MAX_PADDING: usize = 64;
+
+    pub fn load(path: &str) -> Result<Box<Self>, NetworkError> {
+        let bytes = std::fs::read(path).map_err(NetworkError::Io)?;
+
+        if bytes.len() < Self::FILE_SIZE || bytes.len() >= Self::FILE_SIZE + Self::MAX_PADDING {
+            return Err(NetworkError::WrongSize(bytes.len()));
+        }
+
+        let mut values = bytes[..Self::FILE_SIZE]
+            .chunks_exact(2)
+            .map(|value| i16::from_le_bytes([value[0], value[1]]));
+
+        //SAFETY: the network is just i16s, for which all-zeroes is a valid bitpattern
+        let mut network: Box<Self> = unsafe { Box::new_zeroed().assume_init() };
+
+        for weights in network.feature_weights.iter_mut() {
+            weights.0.iter_mut().for_each(|weight| *weight = values.next().unwrap());
+        }
+
+        network
+            .feature_bias
+            .0
+            .iter_mut()
+            .for_each(|bias| *bias = values.next().unwrap());
+
+        for weights in network.output_weights.iter_mut() {
+            weights.0.iter_mut().for_each(|weight| *weight = values.next().unwrap());
+        }
+
+        network.output_bias = values.next().unwrap();
+
+        Ok(network)
+    }
+
+    // from the side to move's perspective
+    #[must_use]
+    pub fn evaluate(&self, acc: &Accumulator, stm: Player) -> Score {
+        let us = &acc.values[stm.idx()].0;
+        let them = &acc.values[stm.flip().idx()].0;
+
+        let sum = screlu_dot(us, &self.output_weights[0].0) + screlu_dot(them, &self.output_weights[1].0);
+
+        (sum / QA + self.output_bias as i32) * SCALE / (QA * QB)
+    }
+}
+
+#[must_use]
+#[allow(clippy::needless_return)]
+fn screlu_dot(inputs: &[i16; HIDDEN_SIZE], weights: &[i16; HIDDEN_SIZE]) -> i32 {
+    #[cfg(target_feature = "avx2")]
+    {
+        //SAFETY: self-explanatory
+        return unsafe { avx2::screlu_dot(inputs, weights) };
+    }
+
+    #[cfg(all(not(target_feature = "avx2"), feature = "dispatch", target_arch = "x86_64"))]
+    if std::arch::is_x86_feature_detected!("avx2") {
+        //SAFETY: we just checked that the cpu supports avx2
+        return unsafe { avx2::screlu_dot(inputs, weights) };
+    }
+
+    #[cfg(not(target_feature = "avx2"))]
+    {
+        scalar::screlu_dot(inputs, weights)
+    }
+}
+
+// top piece, then the owners of the layers below it, relative to the perspective
+fn square_features(pos: &Position, sq: Square, perspective: Player, mut f: impl FnMut(usize)) {
+    let stacks = pos.stacks();
+
+    let Some(top) = stacks.top(sq) else {
+        return;
+    };
+
+    let base = sq.idx() * FEATURES_PER_SQUARE;
+    let relative = |player: Player| (player != perspective) as usize;
+
+    let top_player = stacks.top_player(sq).unwrap();
+    f(base + top.idx() * Player::COUNT + relative(top_player));
+
+    let height = stacks.height(sq) as usize;
+    let players = stacks.players(sq);
+
+    for layer in 0..STACK_LAYERS.min(height - 1) {
+        let player = Player::from_raw(((players >> (height - 2 - layer)) & 0x1) as u8).unwrap();
+        f(base + Piece::COUNT + layer * Player::COUNT + relative(player));
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Accumulator {
+    values: [Align64<[i16; HIDDEN_SIZE]>; Player::COUNT],
+}
+
+impl Accumulator {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            values: [Align64([0; HIDDEN_SIZE]); Player::COUNT],
+        }
+    }
+
+    pub fn refresh(&mut self, network: &Network, pos: &Position) {
+        for perspective in [Player::P1, Player::P2] {
+            let values = &mut self.values[perspective.idx()].0;
+            *values = network.feature_bias.0;
+
+            for sq in pos.occ() {
+                square_features(pos, sq, perspective, |feature| {
+                    add_weights(values, &network.feature_weights[feature].0);
+                });
+            }
+        }
+    }
+
+    // `changed` must cover every square whose stack differs between `old` and `new`
+    // first half of an incremental update, with `old` still before the move
+    pub fn sub_squares(&mut self, network: &Network, prev: &Self, old: &Position, changed: Bitboard) {
+        for perspective in [Player::P1, Player::P2] {
+            let values = &mut self.values[perspective.idx()].0;
+            *values = prev.values[perspective.idx()].0;
+
+            for sq in changed {
+                square_features(old, sq, perspective, |feature| {
+                    sub_weights(values, &network.feature_weights[feature].0);
+                });
+            }
+        }
+    }
+
+    // second half, once the move has been made
+    pub fn add_squares(&mut self, network: &Network, new: &Position, changed: Bitboard) {
+        for perspective in [Player::P1, Player::P2] {
+            let values = &mut self.values[perspective.idx()].0;
+
+            for sq in changed {
+                square_features(new, sq, perspective, |feature| {
+                    add_weights(values, &network.feature_weights[feature].0);
+                });
+            }
+        }
+    }
+}
+
+impl Default for Accumulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// simple enough for the compiler to vectorise
+fn add_weights(values: &mut [i16; HIDDEN_SIZE], weights: &[i16; HIDDEN_SIZE]) {
+    for (value, &weight) in values.iter_mut().zip(weights) {
+        *value = value.wrapping_add(weight);
+    }
+}
+
+fn sub_weights(values: &mut [i16; HIDDEN_SIZE], weights: &[i16; HIDDEN_SIZE]) {
+    for (value, &weight) in values.iter_mut().zip(weights) {
+        *value = value.wrapping_sub(weight);
+    }
+}
@@ -0,0 +1,58 @@
+/*
+ * syntaks, a TEI Tak engine
+ * Copyright (c) 2026 Ciekce
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use super::{HIDDEN_SIZE, QA};
+use std::arch::x86_64::*;
+
+// relies on |clipped * weight| fitting in an i16, which holds for weights trained within (-2, 2)
+#[must_use]
+#[target_feature(enable = "avx2")]
+pub(super) fn screlu_dot(inputs: &[i16; HIDDEN_SIZE], weights: &[i16; HIDDEN_SIZE]) -> i32 {
+    const LANES: usize = size_of::<__m256i>() / size_of::<i16>();
+
+    let zero = _mm256_setzero_si256();
+    let qa = _mm256_set1_epi16(QA as i16);
+
+    let mut sum = _mm256_setzero_si256();
+
+    for idx in (0..HIDDEN_SIZE).step_by(LANES) {
+        //SAFETY: idx + LANES <= HIDDEN_SIZE, and unaligned loads are fine
+        let (input, weight) = unsafe {
+            (
+                _mm256_loadu_si256(inputs.as_ptr().add(idx).cast()),
+                _mm256_loadu_si256(weights.as_ptr().add(idx).cast()),
+            )
+        };
+
+        let clipped = _mm256_min_epi16(_mm256_max_epi16(input, zero), qa);
+        let product = _mm256_madd_epi16(_mm256_mullo_epi16(clipped, weight), clipped);
+
+        sum = _mm256_add_epi32(sum, product);
+    }
+
+    let sum = _mm_add_epi32(_mm256_castsi256_si128(sum), _mm256_extracti128_si256::<1>(sum));
+    let sum = _mm_add_epi32(sum, _mm_unpackhi_epi64(sum, sum));
+    let sum = _mm_add_epi32(sum, _mm_shuffle_epi32::<0b01>(sum));
+
+    _mm_cvtsi128_si32(sum)
+}
@@ -0,0 +1,164 @@
+/*
+ * syntaks, a TEI Tak engine
+ * Copyright (c) 2026 Ciekce
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::search::{SCORE_MATE, Score, SearchResult, is_decisive};
+use crate::takmove::Move;
+use std::sync::Mutex;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ScoreBound {
+    Exact,
+    Upper,
+    Lower,
+}
+
+// in permille
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Wdl {
+    pub win: i32,
+    pub draw: i32,
+    pub loss: i32,
+}
+
+impl Wdl {
+    #[must_use]
+    pub fn from_score(score: Score) -> Self {
+        if is_decisive(score) {
+            return if score > 0 {
+                Self {
+                    win: 1000,
+                    draw: 0,
+                    loss: 0,
+                }
+            } else {
+                Self {
+                    win: 0,
+                    draw: 0,
+                    loss: 1000,
+                }
+            };
+        }
+
+        let p = |cp: Score| {
+            let x = (-cp as f64 + 40.0) / 267.0;
+            (1000.0 / (1.0 + x.exp())).round() as i32
+        };
+
+        let win = p(score);
+        let loss = p(-score);
+
+        Self {
+            win,
+            draw: 1000 - win - loss,
+            loss,
+        }
+    }
+}
+
+// in moves rather than plies, negative when getting mated
+#[must_use]
+pub fn mate_distance(score: Score) -> Option<i32> {
+    if !is_decisive(score) {
+        None
+    } else if score > 0 {
+        Some((SCORE_MATE - score + 1) / 2)
+    } else {
+        Some(-(SCORE_MATE + score) / 2)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PvLine {
+    pub depth: i32,
+    pub seldepth: i32,
+    pub score: Score,
+    pub bound: ScoreBound,
+    pub wdl: Wdl,
+    pub pv: Vec<Move>,
+}
+
+// one report of the current state of the search, ordered best line first
+#[derive(Clone, Debug)]
+pub struct SearchInfo {
+    // in seconds
+    pub time: f64,
+    pub nodes: usize,
+    pub hashfull: usize,
+    // the number of lines searched, some may not have been reached yet
+    pub multipv: usize,
+    pub lines: Vec<PvLine>,
+}
+
+// called from the main search thread
+pub trait SearchObserver: Send + Sync {
+    fn on_iteration(&self, info: &SearchInfo);
+
+    // 1-indexed, across all multipv lines
+    fn on_currmove(&self, _depth: i32, _mv: Move, _move_number: usize) {}
+
+    fn on_best_move(&self, result: &SearchResult);
+}
+
+// keeps everything it is told, for embedding the searcher
+#[derive(Default)]
+pub struct SearchCollector {
+    iterations: Mutex<Vec<SearchInfo>>,
+    result: Mutex<Option<SearchResult>>,
+}
+
+impl SearchCollector {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn iterations(&self) -> Vec<SearchInfo> {
+        self.iterations.lock().unwrap().clone()
+    }
+
+    #[must_use]
+    pub fn last_iteration(&self) -> Option<SearchInfo> {
+        self.iterations.lock().unwrap().last().cloned()
+    }
+
+    #[must_use]
+    pub fn result(&self) -> Option<SearchResult> {
+        *self.result.lock().unwrap()
+    }
+
+    pub fn clear(&self) {
+        self.iterations.lock().unwrap().clear();
+        *self.result.lock().unwrap() = None;
+    }
+}
+
+impl SearchObserver for SearchCollector {
+    fn on_iteration(&self, info: &SearchInfo) {
+        self.iterations.lock().unwrap().push(info.clone());
+    }
+
+    fn on_best_move(&self, result: &SearchResult) {
+        *self.result.lock().unwrap() = Some(*result);
+    }
+}
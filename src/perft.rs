@@ -24,9 +24,36 @@
 use crate::board::Position;
 use crate::movegen::generate_moves;
 use crate::takmove::Move;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Instant;
 
-fn do_perft(pos: &Position, depth: i32, movelists: &mut [Vec<Move>]) -> usize {
+// lines of `tps ; D1 n ; D2 n ...`, blank lines and lines starting with # are skipped
+pub const PERFT_SUITE: &str = "\
+# opening swap
+x4/x4/x4/x4 1 1 ; D1 16 ; D2 240 ; D3 7440 ; D4 216464 ; D5 6468872
+x6/x6/x6/x6/x6/x5,2 2 1 ; D1 35 ; D2 3688 ; D3 372708 ; D4 34400372
+x8/x8/x8/x8/x8/x8/x8/x8 1 1 ; D1 64 ; D2 4032 ; D3 764064
+# wall crushes, alone and at the end of a spread
+x5/x,2S,x3/x,21C,x,2S,x/x2,1S,x2/x5 1 5 ; D1 55 ; D2 3715 ; D3 194027 ; D4 11471484
+x6/x2,12C,x3/x,2S,x,1S,x2/x,21C,x,2S,x2/x6/x6 1 10 ; D1 74 ; D2 5712 ; D3 413919 ; D4 30947453
+x6/x6/x,1S,21C,2S,x2/x2,2S,x3/x6/x6 1 6 ; D1 73 ; D2 7251 ; D3 522802 ; D4 45782940
+# stacks taller than the carry limit
+1212121,x3/x4/x2,2,x/x3,1 1 8 ; D1 56 ; D2 1892 ; D3 84840 ; D4 2951066 ; D5 118733832
+x6/x6/x2,2121212121C,x3/x2,1S,x3/x6/x6 1 12 ; D1 155 ; D2 17529 ; D3 2222252
+x8/x8/x8/x3,121212121212C,x4/x3,2S,x4/x8/x8/x8 1 14 ; D1 186 ; D2 97302 ; D3 21624942
+# nearly out of flats
+11111112222,x3/x2,1111111,x/x4/x,2,x,2 1 10 ; D1 52 ; D2 2775 ; D3 140224 ; D4 6515726
+x5/x,1,x3/x,2222222221,x3/x,22222222221,x,1S,x/x5 2 10 ; D1 63 ; D2 10360 ; D3 998592
+# caps partly or fully used
+x7/x7/x2,1C,x4/x7/x3,2C,x,2C,x/x7/x7 1 4 ; D1 142 ; D2 13916 ; D3 1740447
+x8/x8/x2,1C,x,1C,x3/x8/x8/x3,2C,x4/x8/x8 2 4 ; D1 187 ; D2 23936 ; D3 3953636
+";
+
+pub const DEFAULT_PERFT_SUITE_THREADS: usize = 1;
+
+fn do_perft(pos: &mut Position, depth: i32, movelists: &mut [Vec<Move>]) -> usize {
     if depth <= 0 {
         return 1;
     }
@@ -43,8 +70,32 @@ fn do_perft(pos: &Position, depth: i32, movelists: &mut [Vec<Move>]) -> usize {
     for &mut mv in moves {
         debug_assert!(pos.is_legal(mv));
 
+        let undo = pos.make_move(mv);
+        total += do_perft(pos, depth - 1, movelists);
+        pos.unmake_move(&undo);
+    }
+
+    total
+}
+
+// the old copy-on-apply path, only kept around to compare against
+fn do_perft_copy(pos: &Position, depth: i32, movelists: &mut [Vec<Move>]) -> usize {
+    if depth <= 0 {
+        return 1;
+    }
+
+    let (moves, movelists) = movelists.split_first_mut().unwrap();
+    generate_moves(moves, pos);
+
+    if depth == 1 {
+        return moves.len();
+    }
+
+    let mut total = 0;
+
+    for &mut mv in moves {
         let pos = pos.apply_move(mv);
-        total += do_perft(&pos, depth - 1, movelists);
+        total += do_perft_copy(&pos, depth - 1, movelists);
     }
 
     total
@@ -52,8 +103,31 @@ fn do_perft(pos: &Position, depth: i32, movelists: &mut [Vec<Move>]) -> usize {
 
 #[must_use]
 pub fn perft(pos: &Position, depth: i32) -> usize {
+    let mut pos = *pos;
+    let mut movelists = vec![Vec::with_capacity(256); depth as usize];
+    do_perft(&mut pos, depth.max(1), &mut movelists)
+}
+
+pub fn perft_bench(pos: &Position, depth: i32) {
+    let depth = depth.max(1);
+
     let mut movelists = vec![Vec::with_capacity(256); depth as usize];
-    do_perft(pos, depth.max(1), &mut movelists)
+
+    let start = Instant::now();
+    let copy_nodes = do_perft_copy(pos, depth, &mut movelists);
+    let copy_nps = (copy_nodes as f64 / start.elapsed().as_secs_f64()) as usize;
+
+    let mut pos = *pos;
+
+    let start = Instant::now();
+    let make_nodes = do_perft(&mut pos, depth, &mut movelists);
+    let make_nps = (make_nodes as f64 / start.elapsed().as_secs_f64()) as usize;
+
+    assert_eq!(copy_nodes, make_nodes);
+
+    println!("copy-make:   {} nodes {} nps", copy_nodes, copy_nps);
+    println!("make/unmake: {} nodes {} nps", make_nodes, make_nps);
+    println!("speedup: {:.2}x", make_nps as f64 / copy_nps as f64);
 }
 
 pub fn split_perft(pos: &Position, depth: i32) {
@@ -63,8 +137,10 @@ pub fn split_perft(pos: &Position, depth: i32) {
 
     let start = Instant::now();
 
+    let mut pos = *pos;
+
     let (moves, movelists) = movelists.split_first_mut().unwrap();
-    generate_moves(moves, pos);
+    generate_moves(moves, &pos);
 
     let mut total = 0;
 
@@ -73,8 +149,9 @@ pub fn split_perft(pos: &Position, depth: i32) {
 
         print!("{:9}  ", mv.to_string());
 
-        let pos = pos.apply_move(mv);
-        let value = do_perft(&pos, depth - 1, movelists);
+        let undo = pos.make_move(mv);
+        let value = do_perft(&mut pos, depth - 1, movelists);
+        pos.unmake_move(&undo);
 
         total += value;
         println!("{}", value);
@@ -86,3 +163,169 @@ pub fn split_perft(pos: &Position, depth: i32) {
     println!("total: {}", total);
     println!("{} nps", nps);
 }
+
+#[derive(Debug)]
+pub enum PerftSuiteError {
+    Io(std::io::Error),
+    // the offending line
+    InvalidLine(usize),
+    NoPositions,
+}
+
+impl Display for PerftSuiteError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{}", err),
+            Self::InvalidLine(line) => write!(f, "invalid entry on line {}", line),
+            Self::NoPositions => write!(f, "no positions"),
+        }
+    }
+}
+
+pub struct PerftSuiteConfig {
+    // the built-in suite if none
+    pub file: Option<String>,
+    pub threads: usize,
+}
+
+pub struct PerftSuiteStats {
+    pub positions: usize,
+    pub failed: usize,
+}
+
+struct SuiteEntry {
+    tps: String,
+    pos: Position,
+    // (depth, expected count)
+    counts: Vec<(i32, usize)>,
+}
+
+fn parse_suite_entry(line: &str) -> Option<SuiteEntry> {
+    let mut parts = line.split(';');
+
+    let tps = parts.next()?.trim();
+    let pos = Position::from_str(tps).ok()?;
+
+    let counts = parts
+        .map(|part| {
+            let (depth, count) = part.trim().split_once(' ')?;
+            let depth = depth
+                .strip_prefix('D')?
+                .parse::<i32>()
+                .ok()
+                .filter(|&depth| depth > 0)?;
+            Some((depth, count.trim().parse().ok()?))
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    if counts.is_empty() {
+        return None;
+    }
+
+    Some(SuiteEntry {
+        tps: tps.to_owned(),
+        pos,
+        counts,
+    })
+}
+
+fn parse_suite(suite: &str) -> Result<Vec<SuiteEntry>, PerftSuiteError> {
+    let mut entries = Vec::new();
+
+    for (line_idx, line) in suite.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        entries.push(parse_suite_entry(line).ok_or(PerftSuiteError::InvalidLine(line_idx + 1))?);
+    }
+
+    if entries.is_empty() {
+        return Err(PerftSuiteError::NoPositions);
+    }
+
+    Ok(entries)
+}
+
+// prints a line per position as it finishes, so with several threads they come out of order
+pub fn run_perft_suite(config: &PerftSuiteConfig) -> Result<PerftSuiteStats, PerftSuiteError> {
+    let entries = match &config.file {
+        Some(path) => parse_suite(&std::fs::read_to_string(path).map_err(PerftSuiteError::Io)?)?,
+        None => parse_suite(PERFT_SUITE)?,
+    };
+
+    let start = Instant::now();
+
+    let next = AtomicUsize::new(0);
+    let failed = AtomicUsize::new(0);
+    let total_nodes = AtomicUsize::new(0);
+
+    std::thread::scope(|s| {
+        for _ in 0..config.threads.clamp(1, entries.len()) {
+            s.spawn(|| {
+                loop {
+                    let idx = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(entry) = entries.get(idx) else {
+                        break;
+                    };
+
+                    let entry_start = Instant::now();
+
+                    let mut nodes = 0;
+                    let mut mismatch = None;
+
+                    for &(depth, expected) in &entry.counts {
+                        let actual = perft(&entry.pos, depth);
+                        nodes += actual;
+
+                        if actual != expected {
+                            mismatch = Some((depth, expected, actual));
+                            break;
+                        }
+                    }
+
+                    total_nodes.fetch_add(nodes, Ordering::Relaxed);
+
+                    let time = entry_start.elapsed().as_secs_f64();
+
+                    match mismatch {
+                        None => println!("{:3}  ok    {:.3}s  {}", idx + 1, time, entry.tps),
+                        Some((depth, expected, actual)) => {
+                            failed.fetch_add(1, Ordering::Relaxed);
+                            println!(
+                                "{:3}  FAIL  {:.3}s  {}  depth {} expected {} got {}",
+                                idx + 1,
+                                time,
+                                entry.tps,
+                                depth,
+                                expected,
+                                actual
+                            );
+                        }
+                    }
+                }
+            });
+        }
+    });
+
+    let time = start.elapsed().as_secs_f64();
+    let total_nodes = total_nodes.into_inner();
+
+    let stats = PerftSuiteStats {
+        positions: entries.len(),
+        failed: failed.into_inner(),
+    };
+
+    println!();
+    println!(
+        "{}/{} passed, {} nodes in {:.3}s, {} nps",
+        stats.positions - stats.failed,
+        stats.positions,
+        total_nodes,
+        time,
+        (total_nodes as f64 / time) as usize
+    );
+
+    Ok(stats)
+}
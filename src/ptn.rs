@@ -0,0 +1,484 @@
+/*
+ * syntaks, a TEI Tak engine
+ * Copyright (c) 2026 Ciekce
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::board::{FlatCountOutcome, GameConfig, MAX_CAPS, MAX_FLATS, MAX_HALF_KOMI, MIN_FLATS, Position, TpsError};
+use crate::core::{BoardSize, Player};
+use crate::search::{SCORE_MATE, Score, is_decisive};
+use crate::takmove::{Move, MoveStrError};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+type Tags = Vec<(String, String)>;
+
+const RESULTS: [&str; 8] = ["R-0", "0-R", "F-0", "0-F", "1-0", "0-1", "1/2-1/2", "0-0"];
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum PtnError {
+    UnterminatedTag,
+    InvalidTag,
+    UnterminatedComment,
+    MissingSize,
+    InvalidSize,
+    InvalidKomi,
+    InvalidFlats,
+    InvalidCaps,
+    InvalidTps(TpsError),
+    // the ply of the offending move
+    InvalidMove(usize, MoveStrError),
+    IllegalMove(usize),
+}
+
+#[derive(Clone, Debug)]
+pub struct PtnGame {
+    tags: Tags,
+    start: Position,
+    moves: Vec<Move>,
+}
+
+impl PtnGame {
+    #[must_use]
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    #[must_use]
+    pub fn start(&self) -> &Position {
+        &self.start
+    }
+
+    #[must_use]
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    // from the result tag if there is a valid one, otherwise from the final position
+    #[must_use]
+    pub fn result(&self) -> Option<GameResult> {
+        self.tag("Result")
+            .and_then(|result| result.parse().ok())
+            .or_else(|| GameResult::from_position(&self.replay().0))
+    }
+
+    // the final position, and the keys of every position before it for repetition detection
+    #[must_use]
+    pub fn replay(&self) -> (Position, Vec<u64>) {
+        let mut pos = self.start;
+        let mut key_history = Vec::with_capacity(self.moves.len());
+
+        for &mv in &self.moves {
+            key_history.push(pos.key());
+            pos = pos.apply_move(mv);
+        }
+
+        (pos, key_history)
+    }
+}
+
+#[must_use]
+fn parse_half_komi(value: &str) -> Option<u8> {
+    let komi = value.parse::<f64>().ok()?;
+    let half_komi = komi * 2.0;
+
+    if half_komi.fract() != 0.0 || half_komi < 0.0 || half_komi > MAX_HALF_KOMI as f64 {
+        return None;
+    }
+
+    Some(half_komi as u8)
+}
+
+#[must_use]
+fn strip_annotations(token: &str) -> &str {
+    token.trim_end_matches(['\'', '"', '?', '!'])
+}
+
+fn parse_tags(s: &str) -> Result<(Tags, &str), PtnError> {
+    let mut tags = Vec::new();
+    let mut rest = s.trim_start();
+
+    while let Some(tag) = rest.strip_prefix('[') {
+        let Some(end) = tag.find(']') else {
+            return Err(PtnError::UnterminatedTag);
+        };
+
+        let (name, value) = tag[..end]
+            .trim()
+            .split_once(char::is_whitespace)
+            .ok_or(PtnError::InvalidTag)?;
+
+        let value = value.trim();
+        let value = value
+            .strip_prefix('"')
+            .and_then(|value| value.strip_suffix('"'))
+            .ok_or(PtnError::InvalidTag)?;
+
+        tags.push((name.to_owned(), value.to_owned()));
+
+        rest = tag[(end + 1)..].trim_start();
+    }
+
+    Ok((tags, rest))
+}
+
+// splits a collection of games at the first tag following each game's moves
+#[must_use]
+pub fn split_games(s: &str) -> Vec<&str> {
+    let mut games = Vec::new();
+
+    let mut start = 0;
+    let mut offset = 0;
+    let mut seen_moves = false;
+
+    for line in s.split_inclusive('\n') {
+        let trimmed = line.trim();
+
+        if trimmed.starts_with('[') {
+            if seen_moves {
+                games.push(&s[start..offset]);
+                start = offset;
+                seen_moves = false;
+            }
+        } else if !trimmed.is_empty() {
+            seen_moves = true;
+        }
+
+        offset += line.len();
+    }
+
+    if !s[start..].trim().is_empty() {
+        games.push(&s[start..]);
+    }
+
+    games
+}
+
+fn strip_comments(s: &str) -> Result<String, PtnError> {
+    let mut result = String::with_capacity(s.len());
+    let mut rest = s;
+
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        result.push(' ');
+
+        let Some(end) = rest[start..].find('}') else {
+            return Err(PtnError::UnterminatedComment);
+        };
+
+        rest = &rest[(start + end + 1)..];
+    }
+
+    result.push_str(rest);
+
+    Ok(result)
+}
+
+impl FromStr for PtnGame {
+    type Err = PtnError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (tags, move_text) = parse_tags(s)?;
+
+        let mut game = Self {
+            tags,
+            start: Position::startpos(GameConfig::default()),
+            moves: Vec::new(),
+        };
+
+        let find_tag = |name: &str| game.tag(name);
+
+        let tps = find_tag("TPS");
+
+        let size = match find_tag("Size") {
+            Some(size) => size
+                .parse::<u8>()
+                .ok()
+                .and_then(BoardSize::new)
+                .ok_or(PtnError::InvalidSize)?,
+            None => match tps {
+                Some(tps) => tps.parse::<Position>().map_err(PtnError::InvalidTps)?.size(),
+                None => return Err(PtnError::MissingSize),
+            },
+        };
+
+        let mut config = GameConfig::new(size);
+
+        // no komi tag means no komi
+        config.half_komi = match find_tag("Komi") {
+            Some(komi) => parse_half_komi(komi).ok_or(PtnError::InvalidKomi)?,
+            None => 0,
+        };
+
+        if let Some(flats) = find_tag("Flats") {
+            config.flats = flats
+                .parse::<u8>()
+                .ok()
+                .filter(|flats| (MIN_FLATS..=MAX_FLATS).contains(flats))
+                .ok_or(PtnError::InvalidFlats)?;
+        }
+
+        if let Some(caps) = find_tag("Caps") {
+            config.caps = caps
+                .parse::<u8>()
+                .ok()
+                .filter(|&caps| caps <= MAX_CAPS)
+                .ok_or(PtnError::InvalidCaps)?;
+        }
+
+        game.start = match tps {
+            Some(tps) => {
+                let parts: Vec<&str> = tps.split_ascii_whitespace().collect();
+                Position::from_tps_parts(config, &parts).map_err(PtnError::InvalidTps)?
+            }
+            None => Position::startpos(config),
+        };
+
+        let move_text = strip_comments(move_text)?;
+
+        let mut pos = game.start;
+
+        for token in move_text.split_ascii_whitespace() {
+            if RESULTS.contains(&token) {
+                continue;
+            }
+
+            // move numbers, possibly with the first move attached
+            let token = match token.split_once('.') {
+                Some((number, mv)) if number.bytes().all(|c| c.is_ascii_digit()) => mv,
+                _ => token,
+            };
+
+            // "--" marks a skipped move in some tools, usually the first one of a tps-based game
+            if token.is_empty() || token == "--" {
+                continue;
+            }
+
+            let ply = game.moves.len();
+
+            let mv = strip_annotations(token)
+                .parse::<Move>()
+                .map_err(|err| PtnError::InvalidMove(ply, err))?;
+
+            if !pos.is_legal(mv) {
+                return Err(PtnError::IllegalMove(ply));
+            }
+
+            pos = pos.apply_move(mv);
+            game.moves.push(mv);
+        }
+
+        Ok(game)
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum GameResult {
+    Road(Player),
+    Flats(Player),
+    // resignation, time forfeit and the like
+    Other(Player),
+    Draw,
+}
+
+impl GameResult {
+    // the result of a game that ended in this position, if it has
+    #[must_use]
+    pub fn from_position(pos: &Position) -> Option<Self> {
+        let mover = pos.stm().flip();
+
+        // a move that completes roads for both players wins for the mover
+        if pos.has_road(mover) {
+            return Some(Self::Road(mover));
+        }
+
+        if pos.has_road(mover.flip()) {
+            return Some(Self::Road(mover.flip()));
+        }
+
+        match pos.count_flats() {
+            FlatCountOutcome::None => None,
+            FlatCountOutcome::Draw => Some(Self::Draw),
+            FlatCountOutcome::Win(player) => Some(Self::Flats(player)),
+        }
+    }
+
+    #[must_use]
+    pub fn winner(self) -> Option<Player> {
+        match self {
+            Self::Road(player) | Self::Flats(player) | Self::Other(player) => Some(player),
+            Self::Draw => None,
+        }
+    }
+}
+
+impl Display for GameResult {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let result = match self {
+            Self::Road(Player::P1) => "R-0",
+            Self::Road(Player::P2) => "0-R",
+            Self::Flats(Player::P1) => "F-0",
+            Self::Flats(Player::P2) => "0-F",
+            Self::Other(Player::P1) => "1-0",
+            Self::Other(Player::P2) => "0-1",
+            Self::Draw => "1/2-1/2",
+        };
+
+        write!(f, "{}", result)
+    }
+}
+
+impl FromStr for GameResult {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "R-0" => Ok(Self::Road(Player::P1)),
+            "0-R" => Ok(Self::Road(Player::P2)),
+            "F-0" => Ok(Self::Flats(Player::P1)),
+            "0-F" => Ok(Self::Flats(Player::P2)),
+            "1-0" => Ok(Self::Other(Player::P1)),
+            "0-1" => Ok(Self::Other(Player::P2)),
+            "1/2-1/2" => Ok(Self::Draw),
+            _ => Err(()),
+        }
+    }
+}
+
+// score from the mover's perspective, in the same units as reported over TEI
+#[must_use]
+pub fn score_comment(score: Score, depth: i32) -> String {
+    if is_decisive(score) {
+        if score > 0 {
+            format!("+M{}/{}", (SCORE_MATE - score + 1) / 2, depth)
+        } else {
+            format!("-M{}/{}", (SCORE_MATE + score) / 2, depth)
+        }
+    } else {
+        format!("{:+.2}/{}", score as f64 / 100.0, depth)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PtnWriter {
+    tags: Tags,
+    start: Position,
+    moves: Vec<(Move, Option<String>)>,
+    result: Option<GameResult>,
+}
+
+impl PtnWriter {
+    #[must_use]
+    pub fn new(start: Position) -> Self {
+        Self {
+            tags: Vec::new(),
+            start,
+            moves: Vec::new(),
+            result: None,
+        }
+    }
+
+    // extra tags, written after the ones derived from the game itself
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(tag, _)| tag.eq_ignore_ascii_case(name)) {
+            Some((_, existing)) => *existing = value.to_owned(),
+            None => self.tags.push((name.to_owned(), value.to_owned())),
+        }
+    }
+
+    pub fn set_players(&mut self, p1: &str, p2: &str) {
+        self.set_tag("Player1", p1);
+        self.set_tag("Player2", p2);
+    }
+
+    pub fn set_result(&mut self, result: GameResult) {
+        self.result = Some(result);
+    }
+
+    pub fn push(&mut self, mv: Move, comment: Option<String>) {
+        self.moves.push((mv, comment));
+    }
+}
+
+impl Display for PtnWriter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let config = self.start.config();
+
+        writeln!(f, "[Size \"{}\"]", config.size)?;
+
+        if config.half_komi.is_multiple_of(2) {
+            writeln!(f, "[Komi \"{}\"]", config.half_komi / 2)?;
+        } else {
+            writeln!(f, "[Komi \"{}.5\"]", config.half_komi / 2)?;
+        }
+
+        writeln!(f, "[Flats \"{}\"]", config.flats)?;
+        writeln!(f, "[Caps \"{}\"]", config.caps)?;
+
+        if self.start != Position::startpos(config) {
+            writeln!(f, "[TPS \"{}\"]", self.start.tps())?;
+        }
+
+        if let Some(result) = self.result {
+            writeln!(f, "[Result \"{}\"]", result)?;
+        }
+
+        for (name, value) in &self.tags {
+            writeln!(f, "[{} \"{}\"]", name, value)?;
+        }
+
+        writeln!(f)?;
+
+        let start_ply = self.start.ply() as usize;
+
+        // a game starting with p2 to move gets a placeholder for p1's move
+        if self.start.stm() == Player::P2 && !self.moves.is_empty() {
+            write!(f, "{}. --", start_ply / 2 + 1)?;
+        }
+
+        for (ply, (mv, comment)) in (start_ply..).zip(&self.moves) {
+            if ply.is_multiple_of(2) {
+                if ply > start_ply {
+                    writeln!(f)?;
+                }
+                write!(f, "{}.", ply / 2 + 1)?;
+            }
+
+            write!(f, " {}", mv)?;
+
+            if let Some(comment) = comment {
+                write!(f, " {{{}}}", comment)?;
+            }
+        }
+
+        if !self.moves.is_empty() {
+            writeln!(f)?;
+        }
+
+        if let Some(result) = self.result {
+            writeln!(f, "{}", result)?;
+        }
+
+        Ok(())
+    }
+}
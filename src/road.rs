@@ -21,20 +21,25 @@
  * SOFTWARE.
  */
 
-#[cfg(target_feature = "avx2")]
+#[cfg(any(target_feature = "avx2", all(feature = "dispatch", target_arch = "x86_64")))]
 mod avx2;
 
 #[cfg(all(not(target_feature = "avx2"), target_feature = "sse4.2"))]
 mod sse;
 
+#[cfg(not(any(target_feature = "avx2", target_feature = "sse4.2")))]
+mod scalar;
+
 use crate::bitboard::Bitboard;
+use crate::core::{BoardSize, Direction, Square};
 
 #[must_use]
-pub fn has_road(road_occ: Bitboard) -> bool {
-    let upper_edge = Bitboard::UPPER_EDGE.raw();
-    let lower_edge = Bitboard::LOWER_EDGE.raw();
-    let left_edge = Bitboard::LEFT_EDGE.raw();
-    let right_edge = Bitboard::RIGHT_EDGE.raw();
+#[allow(clippy::needless_return)]
+pub fn has_road(road_occ: Bitboard, size: BoardSize) -> bool {
+    let upper_edge = size.edge(Direction::Up).raw();
+    let lower_edge = size.edge(Direction::Down).raw();
+    let left_edge = size.edge(Direction::Left).raw();
+    let right_edge = size.edge(Direction::Right).raw();
 
     let road_occ = road_occ.raw();
 
@@ -43,8 +48,8 @@ pub fn has_road(road_occ: Bitboard) -> bool {
     let left = road_occ & left_edge;
     let right = road_occ & right_edge;
 
-    let up = up | (up >> 6 & road_occ);
-    let down = down | (down << 6 & road_occ);
+    let up = up | (up >> 8 & road_occ);
+    let down = down | (down << 8 & road_occ);
     let left = left | (left << 1 & road_occ);
     let right = right | (right >> 1 & road_occ);
 
@@ -54,10 +59,226 @@ pub fn has_road(road_occ: Bitboard) -> bool {
         return unsafe { avx2::has_road(road_occ, up, down, left, right) };
     }
 
+    #[cfg(all(not(target_feature = "avx2"), feature = "dispatch", target_arch = "x86_64"))]
+    if std::arch::is_x86_feature_detected!("avx2") {
+        //SAFETY: we just checked that the cpu supports avx2
+        return unsafe { avx2::has_road(road_occ, up, down, left, right) };
+    }
+
     #[cfg(all(not(target_feature = "avx2"), target_feature = "sse4.2"))]
     {
         return unsafe { sse::has_road(road_occ, up, down, left, right) };
     }
 
-    todo!();
+    #[cfg(not(any(target_feature = "avx2", target_feature = "sse4.2")))]
+    {
+        scalar::has_road(road_occ, up, down, left, right)
+    }
+}
+
+// the candidate squares that would complete a road if added to road_occ, which must not
+// already have one. a square does when it touches, or is on, both edges' connected groups
+#[must_use]
+pub fn road_completions(road_occ: Bitboard, candidates: Bitboard, size: BoardSize) -> Bitboard {
+    let left_edge = Bitboard::LEFT_EDGE.raw();
+    let right_edge = Bitboard::RIGHT_EDGE.raw();
+
+    let adjacent = |bb: u64| bb << 8 | bb >> 8 | (bb << 1 & !left_edge) | (bb >> 1 & !right_edge);
+
+    let edges = [Direction::Up, Direction::Down, Direction::Left, Direction::Right].map(|dir| size.edge(dir).raw());
+
+    let road_occ = road_occ.raw();
+
+    // all four floods at once, which the compiler is free to vectorise
+    let mut groups = edges.map(|edge| edge & road_occ);
+
+    loop {
+        let next = groups.map(|group| (group | adjacent(group)) & road_occ);
+        if next == groups {
+            break;
+        }
+
+        groups = next;
+    }
+
+    let near = [0, 1, 2, 3].map(|idx| edges[idx] | adjacent(groups[idx]));
+    let result = (near[0] & near[1]) | (near[2] & near[3]);
+
+    Bitboard::from_raw(result) & candidates
+}
+
+#[must_use]
+fn adjacent(bb: Bitboard) -> Bitboard {
+    bb.shift(Direction::Up) | bb.shift(Direction::Down) | bb.shift(Direction::Left) | bb.shift(Direction::Right)
+}
+
+// kogge-stone fills along ranks and files, so straight runs are covered in one go
+#[must_use]
+fn flood(from: Bitboard, within: Bitboard) -> Bitboard {
+    let within = within.raw();
+
+    let rightwards = within & !Bitboard::LEFT_EDGE.raw();
+    let leftwards = within & !Bitboard::RIGHT_EDGE.raw();
+
+    let mut group = from.raw() & within;
+
+    loop {
+        let prev = group;
+
+        let (mut right, mut left, mut up, mut down) = (group, group, group, group);
+        let (mut right_prop, mut left_prop, mut up_prop, mut down_prop) = (rightwards, leftwards, within, within);
+
+        for shift in [1, 2, 4] {
+            right |= right_prop & (right << shift);
+            left |= left_prop & (left >> shift);
+            up |= up_prop & (up << (shift * 8));
+            down |= down_prop & (down >> (shift * 8));
+
+            right_prop &= right_prop << shift;
+            left_prop &= left_prop >> shift;
+            up_prop &= up_prop << (shift * 8);
+            down_prop &= down_prop >> (shift * 8);
+        }
+
+        group = right | left | up | down;
+
+        if group == prev {
+            return Bitboard::from_raw(group);
+        }
+    }
+}
+
+// one connected group of a player's road squares
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct RoadGroup {
+    squares: Bitboard,
+}
+
+impl RoadGroup {
+    #[must_use]
+    pub fn containing(sq: Square, roads: Bitboard) -> Self {
+        Self {
+            squares: flood(sq.bb(), roads),
+        }
+    }
+
+    #[must_use]
+    pub fn squares(&self) -> Bitboard {
+        self.squares
+    }
+
+    #[must_use]
+    pub fn size(&self) -> u32 {
+        self.squares.popcount()
+    }
+
+    // number of files the group covers
+    #[must_use]
+    pub fn file_span(&self) -> u32 {
+        let mut files = self.squares.raw();
+        files |= files >> 32;
+        files |= files >> 16;
+        files |= files >> 8;
+        (files & 0xff).count_ones()
+    }
+
+    // number of ranks the group covers
+    #[must_use]
+    pub fn rank_span(&self) -> u32 {
+        let mut ranks = self.squares.raw();
+        ranks |= ranks >> 4;
+        ranks |= ranks >> 2;
+        ranks |= ranks >> 1;
+        (ranks & Bitboard::LEFT_EDGE.raw()).count_ones()
+    }
+
+    #[must_use]
+    pub fn is_road(&self, size: BoardSize) -> bool {
+        let touches = |dir| !(self.squares & size.edge(dir)).is_empty();
+        (touches(Direction::Up) && touches(Direction::Down)) || (touches(Direction::Left) && touches(Direction::Right))
+    }
+}
+
+// a player's road squares split into connected groups, kept up to date through moves. only
+// the lowest square of each group is stored, the rest of a group is a cheap flood away
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct RoadGroups {
+    roots: Bitboard,
+    has_road: bool,
+}
+
+impl RoadGroups {
+    #[must_use]
+    pub fn has_road(&self) -> bool {
+        self.has_road
+    }
+
+    #[must_use]
+    pub fn count(&self) -> u32 {
+        self.roots.popcount()
+    }
+
+    // `roads` has to be the road squares these groups were last updated with
+    pub fn iter(&self, roads: Bitboard) -> impl Iterator<Item = RoadGroup> {
+        self.roots.into_iter().map(move |root| RoadGroup {
+            squares: flood(root.bb(), roads),
+        })
+    }
+
+    // `before` and `after` are the player's road squares either side of a move
+    pub fn update(&mut self, before: Bitboard, after: Bitboard, size: BoardSize) {
+        let lost = before & !after;
+
+        if !lost.is_empty() && self.has_road {
+            self.rebuild(after, size);
+            return;
+        }
+
+        let mut changed = after & !before;
+
+        // taking away a square with a single neighbour can't split its group, and only
+        // matters if it was the root. anything else gets its group regrouped
+        let mut split = Bitboard::empty();
+        let mut roads = before;
+
+        for sq in lost {
+            roads.clear_sq(sq);
+
+            let neighbours = adjacent(sq.bb()) & roads;
+
+            if neighbours.is_empty() {
+                self.roots.clear_sq(sq);
+            } else if neighbours.popcount() > 1 || self.roots.has_sq(sq) {
+                split.set_sq(sq);
+            }
+        }
+
+        if !split.is_empty() {
+            let affected = flood(split, before);
+            self.roots &= !affected;
+            changed |= affected & after;
+        }
+
+        while let Some(sq) = changed.lsb() {
+            let group = flood(sq.bb(), after);
+            changed &= !group;
+
+            self.roots = (self.roots & !group).with_sq(group.lsb().unwrap());
+            self.has_road |= RoadGroup { squares: group }.is_road(size);
+        }
+    }
+
+    pub fn rebuild(&mut self, roads: Bitboard, size: BoardSize) {
+        *self = Self::default();
+
+        let mut remaining = roads;
+
+        while let Some(root) = remaining.lsb() {
+            let group = flood(root.bb(), roads);
+            remaining &= !group;
+
+            self.roots.set_sq(root);
+            self.has_road |= RoadGroup { squares: group }.is_road(size);
+        }
+    }
 }
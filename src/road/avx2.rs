@@ -40,8 +40,8 @@ pub(super) fn has_road(road_occ: u64, up: u64, down: u64, left: u64, right: u64)
     let road_occ = _mm256_set1_epi64x(road_occ as i64);
 
     let calc_next_masks = |masks| {
-        let next_masks_u = _mm256_slli_epi64::<6>(masks);
-        let next_masks_d = _mm256_srli_epi64::<6>(masks);
+        let next_masks_u = _mm256_slli_epi64::<8>(masks);
+        let next_masks_d = _mm256_srli_epi64::<8>(masks);
         let next_masks_ud = _mm256_or_si256(next_masks_u, next_masks_d);
 
         let next_masks_l = _mm256_andnot_si256(left_edge, _mm256_slli_epi64::<1>(masks));
@@ -74,9 +74,10 @@ pub(super) fn has_road(road_occ: u64, up: u64, down: u64, left: u64, right: u64)
             return true;
         }
 
-        let new = _mm256_cmpgt_epi64(next_masks, masks);
-        let new = unsafe { std::mem::transmute::<__m256i, __m256d>(new) };
-        let bit = _mm256_movemask_pd(new);
+        // the masks only ever grow, and a signed comparison would break on h8
+        let unchanged = _mm256_cmpeq_epi64(next_masks, masks);
+        let unchanged = unsafe { std::mem::transmute::<__m256i, __m256d>(unchanged) };
+        let bit = _mm256_movemask_pd(unchanged) ^ 0xF;
 
         if (1 << bit) & 0b1111_1000_1000_1000 == 0 {
             return false;
@@ -0,0 +1,50 @@
+/*
+ * syntaks, a TEI Tak engine
+ * Copyright (c) 2026 Ciekce
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::bitboard::Bitboard;
+
+#[must_use]
+fn connects(road_occ: u64, mut from: u64, to: u64) -> bool {
+    let left_edge = Bitboard::LEFT_EDGE.raw();
+    let right_edge = Bitboard::RIGHT_EDGE.raw();
+
+    loop {
+        if from & to != 0 {
+            return true;
+        }
+
+        let next = from | from << 8 | from >> 8 | (from << 1 & !left_edge) | (from >> 1 & !right_edge);
+        let next = next & road_occ;
+
+        if next == from {
+            return false;
+        }
+
+        from = next;
+    }
+}
+
+#[must_use]
+pub(super) fn has_road(road_occ: u64, up: u64, down: u64, left: u64, right: u64) -> bool {
+    connects(road_occ, up, down) || connects(road_occ, left, right)
+}
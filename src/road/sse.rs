@@ -36,8 +36,8 @@ pub(super) fn has_road(road_occ: u64, up: u64, down: u64, left: u64, right: u64)
     let road_occ = _mm_set1_epi64x(road_occ as i64);
 
     let calc_next_masks = |masks| {
-        let next_masks_u = _mm_slli_epi64::<6>(masks);
-        let next_masks_d = _mm_srli_epi64::<6>(masks);
+        let next_masks_u = _mm_slli_epi64::<8>(masks);
+        let next_masks_d = _mm_srli_epi64::<8>(masks);
         let next_masks_ud = _mm_or_si128(next_masks_u, next_masks_d);
 
         let next_masks_l = _mm_andnot_si128(left_edge, _mm_slli_epi64::<1>(masks));
@@ -60,10 +60,11 @@ pub(super) fn has_road(road_occ: u64, up: u64, down: u64, left: u64, right: u64)
             return true;
         }
 
-        let new_ul = _mm_cmpgt_epi64(next_masks_ul, masks_ul);
-        let new_dr = _mm_cmpgt_epi64(next_masks_dr, masks_dr);
+        // the masks only ever grow, and a signed comparison would break on h8
+        let unchanged_ul = _mm_cmpeq_epi64(next_masks_ul, masks_ul);
+        let unchanged_dr = _mm_cmpeq_epi64(next_masks_dr, masks_dr);
 
-        if _mm_testz_si128(new_ul, new_dr) != 0 {
+        if _mm_test_all_ones(_mm_or_si128(unchanged_ul, unchanged_dr)) != 0 {
             return false;
         }
 
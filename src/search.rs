@@ -21,16 +21,20 @@
  * SOFTWARE.
  */
 
+use crate::bitboard::Bitboard;
 use crate::board::Position;
-use crate::core::PieceType;
-use crate::eval::static_eval;
+use crate::core::{Direction, PieceType};
+use crate::eval::EvalParams;
 use crate::limit::Limits;
 use crate::movegen::generate_moves;
 use crate::movepick::Movepicker;
+use crate::nnue::Network;
+use crate::observer::{PvLine, ScoreBound, SearchInfo, SearchObserver, Wdl};
+use crate::road::has_road;
 use crate::takmove::Move;
 use crate::tei::TeiOptions;
 use crate::thread::{PvList, RootMove, SharedContext, TerminalState, ThreadData, update_pv};
-use crate::ttable::TtFlag;
+use crate::ttable::{TtAllocError, TtFlag};
 use crate::util::command_channel::{Receiver, Sender, channel};
 use std::sync::Arc;
 use std::thread;
@@ -62,6 +66,16 @@ pub const fn is_decisive(score: Score) -> bool {
 
 pub const MAX_DEPTH: i32 = 255;
 
+#[derive(Copy, Clone, Debug)]
+pub struct SearchResult {
+    pub best_move: Move,
+    pub score: Score,
+    pub depth: i32,
+}
+
+// evasions can keep answering each other, so qsearch gets cut off eventually
+const MAX_QSEARCH_DEPTH: i32 = 8;
+
 const WIDEN_REPORT_DELAY: f64 = 1.0;
 const VERBOSE_MULTIPV_DELAY: f64 = 1.0;
 const CURRMOVE_REPORT_DELAY: f64 = 2.5;
@@ -156,11 +170,229 @@ impl NodeType for RootNode {
     const ROOT_NODE: bool = true;
 }
 
+// whether a spread could complete a road for the mover, true for some that cannot
+#[must_use]
+fn may_complete_road(pos: &Position, mv: Move) -> bool {
+    debug_assert!(mv.is_spread());
+    let covered = mv.affected_squares().without_sq(mv.sq());
+    has_road(pos.roads(pos.stm()) | covered, pos.size())
+}
+
+// squares our spreads could reach, and where a capstone could crush a wall, per direction
+#[must_use]
+fn spread_reach(pos: &Position) -> (Bitboard, [Bitboard; 4]) {
+    let size = pos.size();
+    let stacks = pos.stacks();
+    let blockers = pos.all_blockers();
+    let walls = pos.piece_bb(PieceType::Wall);
+
+    let mut reach = Bitboard::empty();
+    let mut crushes = [Bitboard::empty(); 4];
+
+    let mut sources = pos.player_bb(pos.stm());
+
+    while let Some(src) = sources.pop_lsb() {
+        let is_cap = stacks.top(src) == Some(PieceType::Capstone);
+        let max_len = stacks.height(src).min(size.carry_limit());
+
+        for dir in [Direction::Up, Direction::Down, Direction::Left, Direction::Right] {
+            let mut sq = src;
+
+            for _ in 0..max_len {
+                let Some(next) = sq.shift_checked(dir).filter(|&next| size.board().has_sq(next)) else {
+                    break;
+                };
+
+                if blockers.has_sq(next) && !(is_cap && walls.has_sq(next)) {
+                    break;
+                }
+
+                reach.set_sq(next);
+                sq = next;
+
+                if walls.has_sq(next) {
+                    crushes[dir.idx()].set_sq(src);
+                    break;
+                }
+            }
+        }
+    }
+
+    (reach, crushes)
+}
+
+// resolves road wins, road threats and wall crushes before trusting the static eval.
+// when the opponent threatens to place a road, every move that could stop it is tried
+// and standing pat is not allowed, otherwise only road-completing spreads and crushes
+fn qsearch(
+    thread: &mut ThreadData,
+    data_stack: &mut [PlyData],
+    pos: &mut Position,
+    ply: i32,
+    qdepth: i32,
+    mut alpha: Score,
+    mut beta: Score,
+) -> Score {
+    if thread.shared().has_stopped() {
+        return 0;
+    }
+
+    if thread.is_main_thread() && thread.root_depth > 1 && thread.shared().check_stop_hard(thread.nodes()) {
+        return 0;
+    }
+
+    alpha = alpha.max(-SCORE_MATE + ply);
+    beta = beta.min(SCORE_MATE - ply);
+    if alpha >= beta {
+        return alpha;
+    }
+
+    thread.inc_nodes();
+    thread.update_seldepth(ply);
+
+    let us = pos.stm();
+    let them = us.flip();
+
+    if !pos.placement_threats(us).is_empty() {
+        return SCORE_MATE - ply - 1;
+    }
+
+    let static_eval = thread.evaluate(pos, ply) + thread.corrhist.correction(pos, &thread.key_history);
+
+    if ply >= MAX_DEPTH || qdepth >= MAX_QSEARCH_DEPTH {
+        return static_eval;
+    }
+
+    let (tt_hit, tt_entry) = thread.shared().tt.probe(pos.key(), ply);
+
+    if tt_hit
+        && match tt_entry.flag {
+            None => unreachable!(),
+            Some(TtFlag::UpperBound) => tt_entry.score <= alpha,
+            Some(TtFlag::LowerBound) => tt_entry.score >= beta,
+            Some(TtFlag::Exact) => true,
+        }
+    {
+        return tt_entry.score;
+    }
+
+    let threats = pos.placement_threats(them);
+    let threatened = !threats.is_empty();
+
+    // a move has to cover one of these to have any chance of stopping the threats
+    let blocking = threats | pos.roads(them);
+
+    let mut best_score = -SCORE_INF;
+
+    // even when threatened, we may be able to spread into a road of our own first
+    let (reach, crushes) = spread_reach(pos);
+    let road_spreads = has_road(pos.roads(us) | reach, pos.size());
+
+    if !threatened {
+        if static_eval >= beta {
+            return static_eval;
+        }
+
+        best_score = static_eval;
+        alpha = alpha.max(static_eval);
+
+        if !road_spreads && crushes.iter().all(|crushes| crushes.is_empty()) {
+            return static_eval;
+        }
+    }
+
+    let (data, child_data) = data_stack.split_first_mut().unwrap();
+
+    data.movelist.clear();
+    generate_moves(&mut data.movelist, pos);
+
+    let mut best_move = None;
+    let mut tt_flag = TtFlag::UpperBound;
+
+    for idx in 0..data.movelist.len() {
+        let mv = data.movelist[idx];
+
+        let is_crush = mv.is_spread()
+            && crushes[mv.dir().idx()].has_sq(mv.sq())
+            && pos.stacks().top(mv.spread_dest()) == Some(PieceType::Wall);
+
+        let may_win = road_spreads && mv.is_spread() && may_complete_road(pos, mv);
+
+        if threatened {
+            let covered = if mv.is_spread() {
+                mv.affected_squares().without_sq(mv.sq()) & blocking
+            } else {
+                mv.sq().bb() & threats
+            };
+
+            if covered.is_empty() && !may_win {
+                continue;
+            }
+        } else if !(is_crush || may_win) {
+            // placements cannot win here, we would have returned already
+            continue;
+        }
+
+        let undo = thread.make_move(ply, pos, mv);
+
+        let score = if let Some(state) = thread.check_terminal_state(ply, pos, mv) {
+            match state {
+                TerminalState::Win => SCORE_MATE - ply - 1,
+                TerminalState::Draw => 0,
+                TerminalState::Loss => -SCORE_MATE + ply + 1,
+            }
+        } else if threatened && !pos.placement_threats(them).is_empty() {
+            // doesn't stop the road, which is then placed
+            -SCORE_MATE + ply + 2
+        } else if !threatened && !is_crush {
+            // didn't complete a road after all
+            thread.unmake_move(pos, &undo);
+            continue;
+        } else {
+            thread.shared().tt.prefetch(pos.key());
+            -qsearch(thread, child_data, pos, ply + 1, qdepth + 1, -beta, -alpha)
+        };
+
+        thread.unmake_move(pos, &undo);
+
+        if thread.shared().has_stopped() {
+            return 0;
+        }
+
+        if score > best_score {
+            best_score = score;
+        }
+
+        if score > alpha {
+            alpha = score;
+            best_move = Some(mv);
+            tt_flag = TtFlag::Exact;
+        }
+
+        if score >= beta {
+            tt_flag = TtFlag::LowerBound;
+            break;
+        }
+    }
+
+    // no winning spread, and every move that could have stopped the threats failed to
+    if best_score == -SCORE_INF {
+        best_score = -SCORE_MATE + ply + 2;
+    }
+
+    thread
+        .shared()
+        .tt
+        .store(pos.key(), best_score, best_move, 0, ply, tt_flag);
+
+    best_score
+}
+
 #[allow(clippy::too_many_arguments)]
 fn search<NT: NodeType>(
     thread: &mut ThreadData,
     data_stack: &mut [PlyData],
-    pos: &Position,
+    pos: &mut Position,
     depth: i32,
     ply: i32,
     mut alpha: Score,
@@ -187,20 +419,18 @@ fn search<NT: NodeType>(
         }
     }
 
-    thread.inc_nodes();
-
     if depth <= 0 {
-        let static_eval = static_eval(pos);
-        let correction = thread.corrhist.correction(pos, &thread.key_history);
-        return static_eval + correction;
+        return qsearch(thread, data_stack, pos, ply, 0, alpha, beta);
     }
 
+    thread.inc_nodes();
+
     if NT::PV_NODE {
         thread.update_seldepth(ply);
     }
 
     if ply > MAX_DEPTH {
-        let static_eval = static_eval(pos);
+        let static_eval = thread.evaluate(pos, ply);
         let correction = thread.corrhist.correction(pos, &thread.key_history);
         return static_eval + correction;
     }
@@ -221,13 +451,19 @@ fn search<NT: NodeType>(
         return tt_entry.score;
     }
 
+    // a road we can place right away
+    if !NT::ROOT_NODE && !pos.placement_threats(pos.stm()).is_empty() {
+        return SCORE_MATE - ply - 1;
+    }
+
     let tt_move = if NT::ROOT_NODE && thread.root_depth > 1 {
         Some(thread.root_moves[thread.pv_idx].mv())
     } else {
-        tt_entry.mv
+        // key collisions can hand us a move from another position
+        tt_entry.mv.filter(|&mv| pos.is_legal(mv))
     };
 
-    let raw_eval = static_eval(pos);
+    let raw_eval = thread.evaluate(pos, ply);
     let correction = thread.corrhist.correction(pos, &thread.key_history);
     let static_eval = raw_eval + correction;
 
@@ -243,23 +479,29 @@ fn search<NT: NodeType>(
         }
 
         // nullmove pruning (nmp)
-        if expected_cutnode && depth >= 4 && static_eval >= beta && thread.stack[ply as usize - 1].mv.is_some() {
+        // passing is pointless if the opponent can then just make a road
+        if expected_cutnode
+            && depth >= 4
+            && static_eval >= beta
+            && thread.stack[ply as usize - 1].mv.is_some()
+            && !pos.has_road_threat(pos.stm().flip())
+        {
             let r = 3 + depth / 4;
 
-            let new_pos = thread.apply_nullmove(ply, pos);
+            thread.make_nullmove(ply, pos);
 
             let score = -search::<NonPvNode>(
                 thread,
                 data_stack,
-                &new_pos,
-                (depth - r).max(1), // dont allow dropping straight to eval
+                pos,
+                (depth - r).max(1), // dont allow dropping straight to qsearch
                 ply + 1,
                 -beta,
                 -beta + 1,
                 false,
             );
 
-            thread.pop_move();
+            thread.unmake_nullmove(pos);
 
             if score >= beta {
                 return if is_win(score) { beta } else { score };
@@ -281,7 +523,6 @@ fn search<NT: NodeType>(
     };
 
     let mut movepicker = Movepicker::new(
-        pos,
         &mut data.movelist,
         &mut data.scores,
         tt_move,
@@ -292,7 +533,7 @@ fn search<NT: NodeType>(
     let mut move_count = 0;
     let mut faillow_moves = arrayvec::ArrayVec::<Move, 32>::new();
 
-    while let Some(mv) = movepicker.next(&thread.history) {
+    while let Some(mv) = movepicker.next(pos, &thread.history) {
         debug_assert!(pos.is_legal(mv));
 
         if NT::ROOT_NODE && !thread.is_legal_root_move(mv) {
@@ -315,27 +556,31 @@ fn search<NT: NodeType>(
             && !thread.shared().options.minimal
             && thread.is_main_thread()
             && thread.shared().elapsed() > CURRMOVE_REPORT_DELAY
+            && let Some(observer) = &thread.shared().observer
         {
             let move_number = thread.pv_idx + move_count;
-            println!("info depth {} currmove {} currmovenumber {}", depth, mv, move_number);
+            observer.on_currmove(depth, mv, move_number);
         }
 
         if NT::PV_NODE {
             child_data[0].pv.clear();
         }
 
-        let new_pos = thread.apply_move(ply, pos, mv);
-        thread.shared().tt.prefetch(new_pos.key());
-
         let is_crush = mv.is_spread() && pos.stacks().top(mv.spread_dest()) == Some(PieceType::Wall);
 
         if is_crush {
             extension += 1;
         }
 
+        let history_score = thread.history.score(pos, mv, prev_move);
+        let prev_fcd = pos.fcd(pos.stm());
+
+        let undo = thread.make_move(ply, pos, mv);
+        thread.shared().tt.prefetch(pos.key());
+
         let nodes_before = thread.nodes();
 
-        let score = if let Some(state) = thread.check_terminal_state(ply, &new_pos, mv) {
+        let score = if let Some(state) = thread.check_terminal_state(ply, pos, mv) {
             match state {
                 TerminalState::Win => SCORE_MATE - ply - 1,
                 TerminalState::Draw => 0,
@@ -350,10 +595,11 @@ fn search<NT: NodeType>(
                 let mut r = LMR_REDUCTIONS[depth as usize - 1][move_count.min(LMR_TABLE_MOVES) - 1];
 
                 r += 1024 * i32::from(!NT::PV_NODE);
-                r -= thread.history.score(pos, mv, prev_move) / 8;
+                r -= history_score / 8;
 
                 if mv.is_spread() {
-                    let gain = new_pos.fcd(pos.stm()) - pos.fcd(pos.stm());
+                    // the side to move has flipped already
+                    let gain = pos.fcd(pos.stm().flip()) - prev_fcd;
                     r += (1 - gain).clamp(0, 3) * 1024;
                 }
 
@@ -361,13 +607,13 @@ fn search<NT: NodeType>(
 
                 let reduced = (new_depth - r).max(1).min(new_depth);
 
-                score = -search::<NonPvNode>(thread, child_data, &new_pos, reduced, ply + 1, -alpha - 1, -alpha, true);
+                score = -search::<NonPvNode>(thread, child_data, pos, reduced, ply + 1, -alpha - 1, -alpha, true);
 
                 if score > alpha && reduced < new_depth {
                     score = -search::<NonPvNode>(
                         thread,
                         child_data,
-                        &new_pos,
+                        pos,
                         new_depth,
                         ply + 1,
                         -alpha - 1,
@@ -379,7 +625,7 @@ fn search<NT: NodeType>(
                 score = -search::<NonPvNode>(
                     thread,
                     child_data,
-                    &new_pos,
+                    pos,
                     new_depth,
                     ply + 1,
                     -alpha - 1,
@@ -389,7 +635,7 @@ fn search<NT: NodeType>(
             }
 
             if NT::PV_NODE && (move_count == 1 || score > alpha) {
-                score = -search::<PvNode>(thread, child_data, &new_pos, new_depth, ply + 1, -beta, -alpha, false);
+                score = -search::<PvNode>(thread, child_data, pos, new_depth, ply + 1, -beta, -alpha, false);
             }
 
             score
@@ -397,7 +643,7 @@ fn search<NT: NodeType>(
 
         let nodes_after = thread.nodes();
 
-        thread.pop_move();
+        thread.unmake_move(pos, &undo);
 
         if thread.shared().has_stopped() {
             return 0;
@@ -496,7 +742,7 @@ fn search<NT: NodeType>(
     best_score
 }
 
-fn run_search(shared: Arc<SharedContext>, ctx: &SearchContext, thread: &mut ThreadData) {
+fn run_search(shared: Arc<SharedContext>, ctx: SearchContext, thread: &mut ThreadData) {
     assert!(thread.shared.is_none());
     thread.shared = Some(shared);
 
@@ -513,9 +759,12 @@ fn run_search(shared: Arc<SharedContext>, ctx: &SearchContext, thread: &mut Thre
     thread.key_history.reserve(ctx.key_history.len());
     thread.key_history.extend_from_slice(&ctx.key_history);
 
+    thread.refresh_root_accumulator(&ctx.root_pos);
+
     counter.register_thread();
 
     let mut data_stack = vec![PlyData::new(); MAX_DEPTH as usize * 2];
+    let mut root_pos = ctx.root_pos;
 
     thread.root_depth = 1;
 
@@ -543,7 +792,7 @@ fn run_search(shared: Arc<SharedContext>, ctx: &SearchContext, thread: &mut Thre
                 let score = search::<RootNode>(
                     thread,
                     &mut data_stack,
-                    &ctx.root_pos,
+                    &mut root_pos,
                     thread.root_depth,
                     0,
                     alpha,
@@ -557,11 +806,14 @@ fn run_search(shared: Arc<SharedContext>, ctx: &SearchContext, thread: &mut Thre
                     break;
                 }
 
-                if thread.is_main_thread() && !thread.shared().options.minimal && ctx.multipv == 1 {
+                if thread.is_main_thread()
+                    && !thread.shared().options.minimal
+                    && ctx.multipv == 1
+                    && let Some(observer) = &thread.shared().observer
+                {
                     let time = thread.shared().elapsed();
                     if time >= WIDEN_REPORT_DELAY {
-                        let nodes = thread.shared().total_nodes();
-                        report_single(thread, time, nodes, ctx.multipv, thread.pv_idx);
+                        observer.on_iteration(&search_info(thread, time, ctx.multipv));
                     }
                 }
 
@@ -581,18 +833,20 @@ fn run_search(shared: Arc<SharedContext>, ctx: &SearchContext, thread: &mut Thre
                 if last_pv
                     && !thread.shared().has_stopped()
                     && (thread.root_depth >= ctx.max_depth
+                        || thread.shared().is_mate_found(thread.pv_move().score)
                         || thread
                             .shared()
                             .check_stop_soft(thread.nodes(), thread.pv_move().nodes as f64 / (thread.nodes() as f64)))
                 {
-                    thread.shared().stop();
+                    thread.shared().set_stopped();
                 }
 
-                if thread.shared().has_stopped()
-                    || (!thread.shared().options.minimal
-                        && (last_pv || thread.shared().elapsed() >= VERBOSE_MULTIPV_DELAY))
+                if let Some(observer) = &thread.shared().observer
+                    && (thread.shared().has_stopped()
+                        || (!thread.shared().options.minimal
+                            && (last_pv || thread.shared().elapsed() >= VERBOSE_MULTIPV_DELAY)))
                 {
-                    report(thread, thread.shared().elapsed(), ctx.multipv);
+                    observer.on_iteration(&search_info(thread, thread.shared().elapsed(), ctx.multipv));
                 }
             }
 
@@ -610,11 +864,14 @@ fn run_search(shared: Arc<SharedContext>, ctx: &SearchContext, thread: &mut Thre
         thread.root_depth += 1;
     }
 
+    // the searcher needs exclusive access to the root moves and key history for the next search
+    drop(ctx);
+
     if thread.is_main_thread() {
         counter.unregister_and_wait();
+        thread.shared().wait_for_ponderhit();
 
-        let time = thread.shared().elapsed();
-        final_report(thread, thread.root_depth, time, ctx.multipv);
+        final_report(thread);
 
         thread.shared = None;
         counter.complete_search();
@@ -624,7 +881,8 @@ fn run_search(shared: Arc<SharedContext>, ctx: &SearchContext, thread: &mut Thre
     }
 }
 
-fn report_single(thread: &ThreadData, time: f64, nodes: usize, multipv: usize, pv_idx: usize) -> bool {
+#[must_use]
+fn pv_line(thread: &ThreadData, pv_idx: usize) -> Option<PvLine> {
     let root_move = &thread.root_moves[pv_idx];
 
     // previous scores are exact, as the depth was completed
@@ -636,92 +894,59 @@ fn report_single(thread: &ThreadData, time: f64, nodes: usize, multipv: usize, p
 
     if score == -SCORE_INF {
         // search stopped at d1 before pv_idx+1 moves were searched
-        return false;
-    }
-
-    assert_ne!(score, -SCORE_INF);
-
-    let ms = (time * 1000.0) as usize;
-    let nps = ((nodes as f64) / time) as usize;
-
-    print!("info ");
-
-    if multipv > 1 {
-        print!("multipv {} ", pv_idx + 1);
+        return None;
     }
 
-    print!(
-        "depth {} seldepth {} time {} nodes {} nps {} score ",
-        root_move.searched_depth, root_move.seldepth, ms, nodes, nps
-    );
+    let bound = match (upper_bound, lower_bound) {
+        (false, false) => ScoreBound::Exact,
+        (true, false) => ScoreBound::Upper,
+        (false, true) => ScoreBound::Lower,
+        (true, true) => unreachable!(),
+    };
 
-    if is_decisive(score) {
-        if score > 0 {
-            print!("mate {}", (SCORE_MATE - score + 1) / 2);
-        } else {
-            print!("mate {}", -(SCORE_MATE + score) / 2);
-        }
-    } else {
-        print!("cp {}", score);
-    }
+    Some(PvLine {
+        depth: root_move.searched_depth,
+        seldepth: root_move.seldepth,
+        score,
+        bound,
+        wdl: Wdl::from_score(score),
+        pv: root_move.pv.iter().copied().collect(),
+    })
+}
 
-    if upper_bound {
-        assert!(!lower_bound);
-        print!(" upperbound");
+#[must_use]
+fn search_info(thread: &ThreadData, time: f64, multipv: usize) -> SearchInfo {
+    SearchInfo {
+        time,
+        nodes: thread.shared().total_nodes(),
+        hashfull: thread.shared().tt.estimate_full_permille(),
+        multipv,
+        lines: (0..multipv).map_while(|pv_idx| pv_line(thread, pv_idx)).collect(),
     }
+}
 
-    if lower_bound {
-        assert!(!upper_bound);
-        print!(" lowerbound");
-    }
+fn final_report(thread: &ThreadData) {
+    let root_move = thread.pv_move();
 
-    if is_decisive(score) {
-        if score > 0 {
-            print!(" wdl 1000 0 0");
-        } else {
-            print!(" wdl 0 0 1000");
-        }
+    let score = if root_move.score == -SCORE_INF {
+        root_move.previous_score
     } else {
-        let p = |cp: Score| {
-            let x = (-cp as f64 + 40.0) / 267.0;
-            (1000.0 / (1.0 + x.exp())).round() as i32
-        };
-
-        let w = p(score);
-        let l = p(-score);
-        let d = 1000 - w - l;
-
-        print!(" wdl {} {} {}", w, d, l);
-    }
-
-    let hashfull = thread.shared().tt.estimate_full_permille();
-    print!(" hashfull {}", hashfull);
-
-    print!(" pv");
-
-    for mv in root_move.pv.iter() {
-        print!(" {}", mv);
-    }
+        root_move.display_score
+    };
 
-    println!();
+    let result = SearchResult {
+        best_move: root_move.mv(),
+        score,
+        depth: root_move.searched_depth,
+    };
 
-    true
-}
+    thread.shared().set_result(result);
 
-fn report(thread: &ThreadData, time: f64, multipv: usize) {
-    let nodes = thread.shared().total_nodes();
-    for pv_idx in 0..multipv {
-        if !report_single(thread, time, nodes, multipv, pv_idx) {
-            break;
-        }
+    if let Some(observer) = &thread.shared().observer {
+        observer.on_best_move(&result);
     }
 }
 
-fn final_report(thread: &ThreadData, _depth: i32, _time: f64, _multipv: usize) {
-    let mv = thread.pv_move().mv();
-    println!("bestmove {}", mv);
-}
-
 #[derive(Clone)]
 #[allow(clippy::large_enum_variant)]
 enum ThreadCommand {
@@ -775,7 +1000,7 @@ impl Searcher {
         loop {
             match receiver.recv(|cmd| cmd.clone()) {
                 ThreadCommand::Ping => {}
-                ThreadCommand::StartSearch(shared, ctx) => run_search(shared, &ctx, &mut data),
+                ThreadCommand::StartSearch(shared, ctx) => run_search(shared, ctx, &mut data),
                 ThreadCommand::Clear => {
                     data.corrhist.clear();
                     data.history.clear();
@@ -796,9 +1021,10 @@ impl Searcher {
         max_depth: i32,
         moves_to_search: &[Move],
         options: &TeiOptions,
+        ponder: bool,
     ) {
         self.modify_shared_ctx(|ctx| {
-            ctx.init_search(options, start_time, limits);
+            ctx.init_search(options, start_time, limits, ponder);
         });
 
         self.init_root_moves(pos, moves_to_search);
@@ -826,10 +1052,39 @@ impl Searcher {
             .send(ThreadCommand::StartSearch(self.shared_ctx.clone(), ctx));
     }
 
+    // runs a search and waits for it to finish, for use outside of tei
+    pub fn search(
+        &mut self,
+        pos: &Position,
+        key_history: &[u64],
+        limits: Limits,
+        max_depth: i32,
+    ) -> Option<SearchResult> {
+        let options = TeiOptions::default();
+
+        self.start_search(
+            pos,
+            key_history,
+            Instant::now(),
+            limits,
+            max_depth,
+            &[],
+            &options,
+            false,
+        );
+        self.wait();
+
+        self.last_result()
+    }
+
     pub fn stop(&mut self) {
         self.shared_ctx.stop();
     }
 
+    pub fn ponderhit(&self) {
+        self.shared_ctx.ponderhit();
+    }
+
     pub fn is_searching(&self) -> bool {
         self.shared_ctx.is_searching()
     }
@@ -838,6 +1093,18 @@ impl Searcher {
         self.shared_ctx.get_counter().wait();
     }
 
+    // the result of the last completed search
+    #[must_use]
+    pub fn last_result(&self) -> Option<SearchResult> {
+        self.shared_ctx.result()
+    }
+
+    // nodes searched by the current or last search, across all threads
+    #[must_use]
+    pub fn nodes(&self) -> usize {
+        self.shared_ctx.total_nodes()
+    }
+
     fn kill_threads(&mut self) {
         self.stop();
         if !self.threads.is_empty() {
@@ -846,12 +1113,12 @@ impl Searcher {
         }
     }
 
-    fn modify_shared_ctx<F>(&mut self, func: F)
+    fn modify_shared_ctx<F, R>(&mut self, func: F) -> R
     where
-        F: Fn(&mut SharedContext),
+        F: FnOnce(&mut SharedContext) -> R,
     {
         let ctx = Arc::get_mut(&mut self.shared_ctx).unwrap();
-        func(ctx);
+        func(ctx)
     }
 
     pub fn reset(&mut self) {
@@ -864,13 +1131,46 @@ impl Searcher {
         self.sender.send(ThreadCommand::Clear);
     }
 
-    pub fn set_tt_size(&mut self, size_mib: usize) {
-        let thread_count = self.threads.len();
+    #[must_use]
+    pub fn eval_params(&self) -> &EvalParams {
+        &self.shared_ctx.eval_params
+    }
+
+    pub fn set_eval_params(&mut self, params: EvalParams) {
+        self.modify_shared_ctx(|ctx| {
+            ctx.eval_params = params;
+        });
+    }
+
+    #[must_use]
+    pub fn network(&self) -> Option<&Network> {
+        self.shared_ctx.network.as_deref()
+    }
+
+    pub fn set_network(&mut self, network: Option<Box<Network>>) {
         self.modify_shared_ctx(|ctx| {
-            ctx.tt.resize(size_mib, thread_count);
+            ctx.network = network;
         });
     }
 
+    // searches are silent without an observer
+    pub fn set_observer(&mut self, observer: Option<Arc<dyn SearchObserver>>) {
+        self.modify_shared_ctx(|ctx| {
+            ctx.observer = observer;
+        });
+    }
+
+    #[must_use]
+    pub fn tt_size_mib(&self) -> usize {
+        self.shared_ctx.tt.size_mib()
+    }
+
+    // keeps the current table if the new one cannot be allocated
+    pub fn set_tt_size(&mut self, size_mib: usize) -> Result<(), TtAllocError> {
+        let thread_count = self.threads.len();
+        self.modify_shared_ctx(|ctx| ctx.tt.resize(size_mib, thread_count))
+    }
+
     pub fn set_threads(&mut self, count: u32) {
         self.kill_threads();
 
@@ -909,14 +1209,11 @@ impl Searcher {
         root_moves.clear();
 
         if !moves_to_search.is_empty() {
-            print!("info string searchmoves:");
             for &mv in moves_to_search {
                 assert!(root_pos.is_legal(mv));
-                print!(" {}", mv);
                 let root_move = RootMove::new(mv);
                 root_moves.push(root_move);
             }
-            println!();
             return;
         }
 
@@ -933,6 +1230,12 @@ impl Searcher {
     }
 }
 
+impl Default for Searcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Drop for Searcher {
     fn drop(&mut self) {
         self.kill_threads();
@@ -0,0 +1,377 @@
+/*
+ * syntaks, a TEI Tak engine
+ * Copyright (c) 2026 Ciekce
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::board::{GameConfig, Position};
+use crate::core::Player;
+use crate::limit::Limits;
+use crate::movegen::generate_moves;
+use crate::nnue::{Network, NetworkError};
+use crate::ptn::{GameResult, PtnWriter, score_comment};
+use crate::search::{MAX_DEPTH, Searcher};
+use crate::takmove::Move;
+use crate::ttable::DEFAULT_TT_SIZE_MIB;
+use crate::util::rng::Sfc64;
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::time::Instant;
+
+pub const DEFAULT_OPENING_PLIES: usize = 4;
+pub const DEFAULT_MAX_PLIES: usize = 1024;
+
+// eval params are compiled in, so only networks can differ between the engines
+#[derive(Clone, Debug)]
+pub struct EngineConfig {
+    pub hash_mib: usize,
+    pub threads: u32,
+    // nnue network file, the classical eval if none
+    pub network: Option<String>,
+    pub nodes: Option<usize>,
+    pub movetime: Option<f64>,
+    // base time and increment per game, in seconds
+    pub time: Option<f64>,
+    pub increment: f64,
+}
+
+impl EngineConfig {
+    #[must_use]
+    pub fn has_limits(&self) -> bool {
+        self.nodes.is_some() || self.movetime.is_some() || self.time.is_some()
+    }
+}
+
+impl Default for EngineConfig {
+    fn default() -> Self {
+        Self {
+            hash_mib: DEFAULT_TT_SIZE_MIB,
+            threads: 1,
+            network: None,
+            nodes: None,
+            movetime: None,
+            time: None,
+            increment: 0.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SelfplayConfig {
+    pub game: GameConfig,
+    pub games: usize,
+    // random plies played before the engines take over, each opening is played with both colours
+    pub opening_plies: usize,
+    // games still running after this many plies are adjudicated as draws
+    pub max_plies: usize,
+    pub seed: u64,
+    pub engines: [EngineConfig; 2],
+    pub out: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum SelfplayError {
+    Io(std::io::Error),
+    Network(String, NetworkError),
+}
+
+impl Display for SelfplayError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{}", err),
+            Self::Network(path, err) => write!(f, "failed to load network '{}': {}", path, err),
+        }
+    }
+}
+
+impl From<std::io::Error> for SelfplayError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+// from the perspective of the first engine
+#[derive(Copy, Clone, Debug, Default)]
+pub struct MatchStats {
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
+}
+
+impl MatchStats {
+    #[must_use]
+    pub fn games(&self) -> usize {
+        self.wins + self.draws + self.losses
+    }
+
+    #[must_use]
+    pub fn score(&self) -> f64 {
+        if self.games() == 0 {
+            return 0.5;
+        }
+
+        (self.wins as f64 + self.draws as f64 / 2.0) / self.games() as f64
+    }
+
+    #[must_use]
+    pub fn elo(&self) -> f64 {
+        score_to_elo(self.score())
+    }
+
+    // 95% confidence interval
+    #[must_use]
+    pub fn elo_error(&self) -> f64 {
+        if self.games() == 0 {
+            return 0.0;
+        }
+
+        let games = self.games() as f64;
+        let score = self.score();
+
+        let variance = (self.wins as f64 * (1.0 - score).powi(2)
+            + self.draws as f64 * (0.5 - score).powi(2)
+            + self.losses as f64 * score.powi(2))
+            / games;
+
+        let deviation = 1.959964 * (variance / games).sqrt();
+
+        (score_to_elo(score + deviation) - score_to_elo(score - deviation)) / 2.0
+    }
+
+    // likelihood of superiority
+    #[must_use]
+    pub fn los(&self) -> f64 {
+        let wins = self.wins as f64;
+        let losses = self.losses as f64;
+
+        if wins + losses == 0.0 {
+            return 0.5;
+        }
+
+        0.5 * (1.0 + erf((wins - losses) / (2.0 * (wins + losses)).sqrt()))
+    }
+
+    fn record(&mut self, result: GameResult, engine1_player: Player) {
+        match result.winner() {
+            Some(winner) if winner == engine1_player => self.wins += 1,
+            Some(_) => self.losses += 1,
+            None => self.draws += 1,
+        }
+    }
+}
+
+#[must_use]
+// clamped so that perfect scores and wide intervals stay finite
+fn score_to_elo(score: f64) -> f64 {
+    let score = score.clamp(1e-6, 1.0 - 1e-6);
+    -400.0 * (1.0 / score - 1.0).log10()
+}
+
+// abramowitz and stegun 7.1.26
+#[must_use]
+fn erf(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.3275911 * x.abs());
+    let poly = t * (0.254829592 + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    (1.0 - poly * (-x * x).exp()).copysign(x)
+}
+
+// uniformly random moves from the start position, never ending the game
+#[must_use]
+pub fn random_opening(game: GameConfig, plies: usize, rng: &mut Sfc64) -> Vec<Move> {
+    let mut moves = Vec::with_capacity(256);
+    let mut opening = Vec::with_capacity(plies);
+
+    'retry: loop {
+        let mut pos = Position::startpos(game);
+        opening.clear();
+
+        for _ in 0..plies {
+            generate_moves(&mut moves, &pos);
+
+            let mv = moves[rng.next_u32_bounded(moves.len() as u32) as usize];
+            pos = pos.apply_move(mv);
+
+            if GameResult::from_position(&pos).is_some() {
+                continue 'retry;
+            }
+
+            opening.push(mv);
+        }
+
+        return opening;
+    }
+}
+
+struct GameRecord {
+    ptn: PtnWriter,
+    result: GameResult,
+}
+
+fn play_game(
+    config: &SelfplayConfig,
+    searchers: &mut [Searcher; 2],
+    opening: &[Move],
+    engine1_player: Player,
+) -> GameRecord {
+    let engine_idx = |player: Player| if player == engine1_player { 0 } else { 1 };
+
+    let start = Position::startpos(config.game);
+
+    let mut ptn = PtnWriter::new(start);
+
+    match engine1_player {
+        Player::P1 => ptn.set_players("engine1", "engine2"),
+        Player::P2 => ptn.set_players("engine2", "engine1"),
+    }
+
+    let mut pos = start;
+    let mut key_history = Vec::with_capacity(config.max_plies);
+
+    for &mv in opening {
+        ptn.push(mv, None);
+        key_history.push(pos.key());
+        pos = pos.apply_move(mv);
+    }
+
+    for searcher in searchers.iter_mut() {
+        searcher.reset();
+    }
+
+    let mut clocks = config.engines.each_ref().map(|engine| engine.time);
+
+    let result = loop {
+        if let Some(result) = GameResult::from_position(&pos) {
+            break result;
+        }
+
+        let repetitions = key_history.iter().filter(|&&key| key == pos.key()).count();
+        if repetitions >= 2 || key_history.len() >= config.max_plies {
+            break GameResult::Draw;
+        }
+
+        let idx = engine_idx(pos.stm());
+        let engine = &config.engines[idx];
+
+        let start_time = Instant::now();
+        let mut limits = Limits::new(start_time);
+
+        if let Some(nodes) = engine.nodes {
+            limits.set_nodes(nodes);
+        }
+
+        if let Some(movetime) = engine.movetime {
+            limits.set_movetime(movetime);
+        }
+
+        if let Some(remaining) = clocks[idx] {
+            limits.set_time_manager(remaining, engine.increment, None);
+        }
+
+        let search_result = searchers[idx].search(&pos, &key_history, limits, MAX_DEPTH).unwrap();
+
+        if let Some(remaining) = &mut clocks[idx] {
+            *remaining -= start_time.elapsed().as_secs_f64();
+
+            if *remaining < 0.0 {
+                break GameResult::Other(pos.stm().flip());
+            }
+
+            *remaining += engine.increment;
+        }
+
+        ptn.push(
+            search_result.best_move,
+            Some(score_comment(search_result.score, search_result.depth)),
+        );
+
+        key_history.push(pos.key());
+        pos = pos.apply_move(search_result.best_move);
+    };
+
+    ptn.set_result(result);
+
+    GameRecord { ptn, result }
+}
+
+pub fn run_selfplay(config: &SelfplayConfig) -> Result<MatchStats, SelfplayError> {
+    let mut networks = [None, None];
+    for (network, engine) in networks.iter_mut().zip(&config.engines) {
+        if let Some(path) = &engine.network {
+            *network = Some(Network::load(path).map_err(|err| SelfplayError::Network(path.clone(), err))?);
+        }
+    }
+
+    let mut out = match &config.out {
+        Some(path) => Some(BufWriter::new(File::create(path)?)),
+        None => None,
+    };
+
+    let mut searchers = config.engines.each_ref().map(|engine| {
+        let mut searcher = Searcher::new();
+
+        searcher.set_threads(engine.threads);
+        if let Err(err) = searcher.set_tt_size(engine.hash_mib) {
+            println!("info string Error: {}, using the default size", err);
+        }
+
+        searcher
+    });
+
+    for (searcher, network) in searchers.iter_mut().zip(networks) {
+        searcher.set_network(network);
+    }
+
+    let mut rng = Sfc64::new(config.seed);
+    let mut stats = MatchStats::default();
+
+    let mut opening = Vec::new();
+
+    for game in 0..config.games {
+        // each opening is played twice, with the engines swapping colours
+        let engine1_player = if game.is_multiple_of(2) {
+            opening = random_opening(config.game, config.opening_plies, &mut rng);
+            Player::P1
+        } else {
+            Player::P2
+        };
+
+        let record = play_game(config, &mut searchers, &opening, engine1_player);
+
+        stats.record(record.result, engine1_player);
+
+        if let Some(out) = &mut out {
+            writeln!(out, "{}", record.ptn)?;
+            out.flush()?;
+        }
+
+        println!(
+            "Finished game {} ({}): engine1 {} - {} - {} engine2 [{:.3}]",
+            game + 1,
+            record.result,
+            stats.wins,
+            stats.losses,
+            stats.draws,
+            stats.score()
+        );
+    }
+
+    Ok(stats)
+}
@@ -21,6 +21,7 @@
  * SOFTWARE.
  */
 
+use crate::bitboard::Bitboard;
 use crate::board::Position;
 use crate::core::*;
 use std::fmt::{Display, Formatter};
@@ -34,7 +35,7 @@ pub struct Move {
 
 impl Move {
     const SQUARE_BITS: usize = 6;
-    const PATTERN_BITS: usize = 6;
+    const PATTERN_BITS: usize = Position::CARRY_LIMIT as usize;
     const FLAG_BITS: usize = 2;
     pub const TOTAL_BITS: usize = Self::SQUARE_BITS + Self::PATTERN_BITS + Self::FLAG_BITS;
 
@@ -124,6 +125,22 @@ impl Move {
         self.pattern().count_ones() as u8
     }
 
+    // just the destination for placements
+    #[must_use]
+    pub fn affected_squares(self) -> Bitboard {
+        let mut result = self.sq().bb();
+
+        if self.is_spread() {
+            let mut sq = self.sq();
+            for _ in 0..self.spread_length() {
+                sq = sq.shift(self.dir()).unwrap();
+                result.set_sq(sq);
+            }
+        }
+
+        result
+    }
+
     #[must_use]
     pub const fn spread_dest(self) -> Square {
         assert!(self.is_spread());
@@ -142,7 +159,7 @@ impl Display for Move {
             let pattern = self.pattern();
 
             let dropped = pattern.trailing_zeros();
-            let taken = 6 - dropped;
+            let taken = Position::CARRY_LIMIT as u32 - dropped;
 
             if taken == 1 {
                 write!(f, "{}{}", self.sq(), self.dir())?;
@@ -246,22 +263,32 @@ impl FromStr for Move {
         next += 1;
 
         let taken = taken.unwrap_or(1);
+        if taken > Position::CARRY_LIMIT {
+            return Err(MoveStrError::TooManySpreadPieces);
+        }
+
         let bytes = bytes.strip_suffix(b"*").unwrap_or(bytes);
 
-        if (s.len() - next) > 6 {
+        if (s.len() - next) > Position::CARRY_LIMIT as usize {
             return Err(MoveStrError::TooManySpreadSteps);
         }
 
         let mut pattern = 1;
         let mut bit = 1;
+        let mut total_dropped = 0;
 
         for &pattern_char in &bytes[next..] {
-            if !(b'1'..=b'6').contains(&pattern_char) {
+            if !(b'1'..=b'8').contains(&pattern_char) {
                 return Err(MoveStrError::InvalidSpreadPattern);
             }
 
             let dropped = pattern_char - b'0';
 
+            total_dropped += dropped;
+            if total_dropped > Position::CARRY_LIMIT {
+                return Err(MoveStrError::TooManySpreadPieces);
+            }
+
             bit <<= dropped;
             pattern |= bit;
         }
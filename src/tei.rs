@@ -21,16 +21,28 @@
  * SOFTWARE.
  */
 
+use crate::bench::{DEFAULT_BENCH_DEPTH, run_bench};
 use crate::board::*;
-use crate::core::Player;
-use crate::eval::static_eval;
+use crate::book::{Book, BookConfig, DEFAULT_BOOK_DEPTH, DEFAULT_BOOK_MIN_GAMES, MAX_BOOK_DEPTH, build_book};
+use crate::core::{BoardSize, Player};
+use crate::datagen::{DEFAULT_DATAGEN_NODES, DEFAULT_DATAGEN_OPENING_PLIES, DataFormat, DatagenConfig, run_datagen};
+use crate::eval::{EvalParams, eval_breakdown, static_eval};
 use crate::limit::Limits;
-use crate::perft::{perft, split_perft};
+use crate::nnue::{Accumulator, Network};
+use crate::observer::{ScoreBound, SearchInfo, SearchObserver, mate_distance};
+use crate::perft::{DEFAULT_PERFT_SUITE_THREADS, PerftSuiteConfig, perft, perft_bench, run_perft_suite, split_perft};
+use crate::ptn::PtnGame;
 use crate::search;
-use crate::search::{MAX_THREADS, Searcher};
+use crate::search::{MAX_THREADS, SearchResult, Searcher};
+use crate::selfplay::{DEFAULT_MAX_PLIES, DEFAULT_OPENING_PLIES, EngineConfig, SelfplayConfig, run_selfplay};
+use crate::takmove::Move;
+use crate::tinue::{DEFAULT_TINUE_DEPTH, DEFAULT_TINUE_NODES, MAX_TINUE_DEPTH, TinueResult, solve_tinue};
 use crate::ttable::{DEFAULT_TT_SIZE_MIB, MAX_TT_SIZE_MIB};
-use std::sync::atomic::Ordering;
-use std::time::Instant;
+use crate::tune::{DEFAULT_TUNE_EPOCHS, DEFAULT_TUNE_LEARNING_RATE, TuneConfig, run_tune};
+use crate::util::rng::Sfc64;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
 const NAME: &str = "syntaks";
 const AUTHORS: &str = "Ciekce";
@@ -55,22 +67,155 @@ impl Default for TeiOptions {
     }
 }
 
+// prints search output as tei info lines
+struct TeiObserver;
+
+impl SearchObserver for TeiObserver {
+    fn on_iteration(&self, info: &SearchInfo) {
+        let ms = (info.time * 1000.0) as usize;
+        let nps = ((info.nodes as f64) / info.time) as usize;
+
+        for (pv_idx, line) in info.lines.iter().enumerate() {
+            print!("info ");
+
+            if info.multipv > 1 {
+                print!("multipv {} ", pv_idx + 1);
+            }
+
+            print!(
+                "depth {} seldepth {} time {} nodes {} nps {} score ",
+                line.depth, line.seldepth, ms, info.nodes, nps
+            );
+
+            match mate_distance(line.score) {
+                Some(moves) => print!("mate {}", moves),
+                None => print!("cp {}", line.score),
+            }
+
+            match line.bound {
+                ScoreBound::Exact => {}
+                ScoreBound::Upper => print!(" upperbound"),
+                ScoreBound::Lower => print!(" lowerbound"),
+            }
+
+            print!(" wdl {} {} {}", line.wdl.win, line.wdl.draw, line.wdl.loss);
+            print!(" hashfull {}", info.hashfull);
+
+            print!(" pv");
+
+            for mv in &line.pv {
+                print!(" {}", mv);
+            }
+
+            println!();
+        }
+    }
+
+    fn on_currmove(&self, depth: i32, mv: Move, move_number: usize) {
+        println!("info depth {} currmove {} currmovenumber {}", depth, mv, move_number);
+    }
+
+    fn on_best_move(&self, result: &SearchResult) {
+        println!("bestmove {}", result.best_move);
+    }
+}
+
+// reports a value that doesn't parse
+#[must_use]
+fn parse_value<T: FromStr>(name: &str, value: &str) -> Option<T> {
+    let parsed = value.parse().ok();
+
+    if parsed.is_none() {
+        eprintln!("Invalid value '{}' for '{}'", value, name);
+    }
+
+    parsed
+}
+
+// walks name/value pairs, applying each. apply returns None after reporting an
+// invalid value, and Some(false) for unknown names. false if any setting failed
+#[must_use]
+fn parse_settings<'a>(args: &[&'a str], kind: &str, mut apply: impl FnMut(&'a str, &'a str) -> Option<bool>) -> bool {
+    for pair in args.chunks(2) {
+        let name = pair[0];
+
+        let Some(&value) = pair.get(1) else {
+            eprintln!("Missing value for '{}'", name);
+            return false;
+        };
+
+        match apply(name, value) {
+            Some(true) => {}
+            Some(false) => {
+                eprintln!("Unknown {} '{}'", kind, name);
+                return false;
+            }
+            None => return false,
+        }
+    }
+
+    true
+}
+
 struct TeiHandler {
     pos: Position,
     key_history: Vec<u64>,
     searcher: Searcher,
     options: TeiOptions,
+    size: BoardSize,
+    // reserves explicitly set through options, otherwise the standard ones for the board size
+    flats: Option<u8>,
+    caps: Option<u8>,
+    half_komi: u8,
+    book: Option<Book>,
+    own_book: bool,
+    book_depth: u16,
+    // for picking book moves
+    rng: Sfc64,
 }
 
 impl TeiHandler {
     #[must_use]
     fn new() -> Self {
         Self {
-            pos: Position::startpos(),
+            pos: Position::startpos(GameConfig::default()),
             key_history: Vec::with_capacity(1024),
-            searcher: Searcher::new(),
+            searcher: {
+                let mut searcher = Searcher::new();
+                searcher.set_observer(Some(Arc::new(TeiObserver)));
+                searcher
+            },
             options: TeiOptions::default(),
+            size: BoardSize::DEFAULT,
+            flats: None,
+            caps: None,
+            half_komi: DEFAULT_HALF_KOMI,
+            book: None,
+            own_book: false,
+            book_depth: DEFAULT_BOOK_DEPTH,
+            rng: Sfc64::new(
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |time| time.as_nanos() as u64),
+            ),
+        }
+    }
+
+    #[must_use]
+    fn config(&self) -> GameConfig {
+        let mut config = GameConfig::new(self.size);
+
+        config.half_komi = self.half_komi;
+
+        if let Some(flats) = self.flats {
+            config.flats = flats;
         }
+
+        if let Some(caps) = self.caps {
+            config.caps = caps;
+        }
+
+        config
     }
 
     fn run(&mut self) {
@@ -98,11 +243,20 @@ impl TeiHandler {
                 "position" => self.handle_position(args),
                 "go" => self.handle_go(args, start_time),
                 "stop" => self.handle_stop(),
+                "ponderhit" => self.handle_ponderhit(),
                 "move" => self.handle_move(args),
                 "wait" => self.handle_wait(),
                 "d" => self.handle_d(),
+                "eval" => self.handle_eval(),
                 "perft" => self.handle_perft(args),
                 "splitperft" => self.handle_splitperft(args),
+                "perftbench" => self.handle_perftbench(args),
+                "perftsuite" => self.handle_perftsuite(args),
+                "selfplay" => self.handle_selfplay(args),
+                "datagen" => self.handle_datagen(args),
+                "tune" => self.handle_tune(args),
+                "book" => self.handle_book(args),
+                "bench" => self.handle_bench(args),
                 "quit" => break,
                 unknown => eprintln!("Unknown command '{}'", unknown),
             }
@@ -112,22 +266,24 @@ impl TeiHandler {
     }
 
     fn handle_tei(&self) {
-        let half_komi = Position::KOMI * 2;
-
         println!("id name {} {}", NAME, VERSION);
         println!("id author {}", AUTHORS);
 
         println!(
-            "option name HalfKomi type spin default {} min {} max {}",
-            half_komi, half_komi, half_komi
+            "option name HalfKomi type spin default {} min 0 max {}",
+            DEFAULT_HALF_KOMI, MAX_HALF_KOMI
         );
         println!(
             "option name Flats type spin default {} min {} max {}",
-            DEFAULT_FLATS, MIN_FLATS, MAX_FLATS
+            BoardSize::DEFAULT.default_flats(),
+            MIN_FLATS,
+            MAX_FLATS
         );
         println!(
             "option name Caps type spin default {} min {} max {}",
-            DEFAULT_CAPS, MIN_CAPS, MAX_CAPS
+            BoardSize::DEFAULT.default_caps(),
+            MIN_CAPS,
+            MAX_CAPS
         );
         println!(
             "option name Hash type spin default {} min 1 max {}",
@@ -137,6 +293,14 @@ impl TeiHandler {
         println!("option name MultiPV type spin default 1 min 1 max {}", MAX_MULTIPV);
         println!("option name Minimal type check default false");
         println!("option name ShowCurrMove type check default false");
+        println!("option name EvalParams type string default <empty>");
+        println!("option name EvalFile type string default <empty>");
+        println!("option name OwnBook type check default false");
+        println!("option name BookFile type string default <empty>");
+        println!(
+            "option name BookDepth type spin default {} min 0 max {}",
+            DEFAULT_BOOK_DEPTH, MAX_BOOK_DEPTH
+        );
 
         println!("teiok");
     }
@@ -148,19 +312,21 @@ impl TeiHandler {
         }
 
         if args.is_empty() {
-            println!("info string Missing size, assuming 6x6");
+            println!("info string Missing size, assuming {0}x{0}", BoardSize::DEFAULT);
+            self.size = BoardSize::DEFAULT;
         } else {
-            match args[0].parse::<u32>() {
-                Ok(size) => {
-                    if size != 6 {
-                        eprintln!("Only 6x6 supported");
-                        return;
-                    }
+            match args[0].parse::<u8>().ok().and_then(BoardSize::new) {
+                Some(size) => self.size = size,
+                None => {
+                    eprintln!("Unsupported size '{}'", args[0]);
+                    return;
                 }
-                Err(_) => eprintln!("Invalid size"),
             }
         }
 
+        self.pos = Position::startpos(self.config());
+        self.key_history.clear();
+
         self.searcher.reset();
     }
 
@@ -207,28 +373,28 @@ impl TeiHandler {
 
         match name.as_str() {
             "halfkomi" => {
-                if let Ok(half_komi) = value.parse::<u32>()
-                    && half_komi != Position::KOMI * 2
-                {
-                    eprintln!("Unsupported komi value");
+                if let Ok(half_komi) = value.parse::<u8>() {
+                    self.half_komi = half_komi.min(MAX_HALF_KOMI);
                 }
             }
             "flats" => {
                 if let Ok(flats) = value.parse::<u8>() {
                     let flats = flats.clamp(MIN_FLATS, MAX_FLATS);
-                    FLATS.store(flats, Ordering::Release);
+                    self.flats = Some(flats);
                 }
             }
             "caps" => {
                 if let Ok(caps) = value.parse::<u8>() {
                     let caps = caps.clamp(MIN_CAPS, MAX_CAPS);
-                    CAPS.store(caps, Ordering::Release);
+                    self.caps = Some(caps);
                 }
             }
             "hash" => {
                 if let Ok(size) = value.parse::<usize>() {
                     let size = size.clamp(1, MAX_TT_SIZE_MIB);
-                    self.searcher.set_tt_size(size);
+                    if let Err(err) = self.searcher.set_tt_size(size) {
+                        println!("info string Error: {}, keeping the previous one", err);
+                    }
                 }
             }
             "threads" => {
@@ -253,6 +419,57 @@ impl TeiHandler {
                     self.options.show_curr_move = show_curr_move;
                 }
             }
+            "evalparams" => {
+                if value == "<empty>" {
+                    self.searcher.set_eval_params(EvalParams::default());
+                    return;
+                }
+
+                let params = match std::fs::read_to_string(&value) {
+                    Ok(params) => params,
+                    Err(err) => {
+                        eprintln!("Failed to read eval params '{}': {}", value, err);
+                        return;
+                    }
+                };
+
+                match params.parse::<EvalParams>() {
+                    Ok(params) => self.searcher.set_eval_params(params),
+                    Err(err) => eprintln!("Failed to parse eval params: {:?}", err),
+                }
+            }
+            "evalfile" => {
+                if value == "<empty>" {
+                    self.searcher.set_network(None);
+                    return;
+                }
+
+                match Network::load(&value) {
+                    Ok(network) => self.searcher.set_network(Some(network)),
+                    Err(err) => eprintln!("Failed to load network '{}': {}", value, err),
+                }
+            }
+            "ownbook" => {
+                if let Ok(own_book) = value.parse::<bool>() {
+                    self.own_book = own_book;
+                }
+            }
+            "bookfile" => {
+                if value == "<empty>" {
+                    self.book = None;
+                    return;
+                }
+
+                match Book::load(&value) {
+                    Ok(book) => self.book = Some(book),
+                    Err(err) => eprintln!("Failed to load book '{}': {}", value, err),
+                }
+            }
+            "bookdepth" => {
+                if let Ok(depth) = value.parse::<u16>() {
+                    self.book_depth = depth.min(MAX_BOOK_DEPTH);
+                }
+            }
             unknown => eprintln!("Unknown option '{}'", unknown),
         }
     }
@@ -277,7 +494,7 @@ impl TeiHandler {
 
         match pos_type {
             "startpos" => {
-                self.pos = Position::startpos();
+                self.pos = Position::startpos(self.config());
                 self.key_history.clear();
             }
             "tps" => {
@@ -288,7 +505,7 @@ impl TeiHandler {
                     return;
                 }
 
-                match Position::from_tps_parts(&args[0..count]) {
+                match Position::from_tps_parts(self.config(), &args[0..count]) {
                     Ok(pos) => {
                         self.pos = pos;
                         self.key_history.clear();
@@ -301,6 +518,36 @@ impl TeiHandler {
 
                 next += count;
             }
+            "ptn" => {
+                let count = args.iter().position(|&s| s == "moves").unwrap_or(args.len());
+
+                if count == 0 {
+                    eprintln!("Missing PTN file");
+                    return;
+                }
+
+                let path = args[0..count].join(" ");
+
+                let ptn = match std::fs::read_to_string(&path) {
+                    Ok(ptn) => ptn,
+                    Err(err) => {
+                        eprintln!("Failed to read PTN file '{}': {}", path, err);
+                        return;
+                    }
+                };
+
+                match ptn.parse::<PtnGame>() {
+                    Ok(game) => {
+                        (self.pos, self.key_history) = game.replay();
+                    }
+                    Err(err) => {
+                        eprintln!("Failed to parse PTN: {:?}", err);
+                        return;
+                    }
+                }
+
+                next += count;
+            }
             _ => {
                 eprintln!("Invalid position type {}", pos_type);
                 return;
@@ -335,6 +582,11 @@ impl TeiHandler {
             return;
         }
 
+        if args.first() == Some(&"tinue") {
+            self.handle_go_tinue(&args[1..], start_time);
+            return;
+        }
+
         let mut limits = Limits::new(start_time);
         let mut max_depth = None;
 
@@ -343,6 +595,9 @@ impl TeiHandler {
         let mut winc = None;
         let mut binc = None;
 
+        let mut moves_to_go = None;
+        let mut ponder = false;
+
         let mut moves_to_search = Vec::new();
 
         let mut i = 0;
@@ -350,6 +605,7 @@ impl TeiHandler {
             let limit_str = args[i];
             match limit_str {
                 "infinite" => {}
+                "ponder" => ponder = true,
                 "depth" => {
                     i += 1;
                     if i >= args.len() {
@@ -431,6 +687,43 @@ impl TeiHandler {
                         return;
                     }
                 }
+                "movestogo" => {
+                    i += 1;
+                    if i >= args.len() {
+                        eprintln!("Missing move count");
+                        return;
+                    }
+
+                    if let Ok(moves) = args[i].parse::<u32>() {
+                        if moves_to_go.is_some() {
+                            eprintln!("Duplicate movestogo limits");
+                            return;
+                        }
+                        moves_to_go = Some(moves);
+                    } else {
+                        eprintln!("Invalid move count '{}'", args[i]);
+                        return;
+                    }
+                }
+                "mate" => {
+                    i += 1;
+                    if i >= args.len() {
+                        eprintln!("Missing mate distance");
+                        return;
+                    }
+
+                    if let Ok(moves) = args[i].parse::<i32>()
+                        && moves > 0
+                    {
+                        if !limits.set_mate(moves) {
+                            eprintln!("Duplicate mate limits");
+                            return;
+                        }
+                    } else {
+                        eprintln!("Invalid mate distance '{}'", args[i]);
+                        return;
+                    }
+                }
                 "searchmoves" => {
                     while i + 1 < args.len() {
                         let candidate = args[i + 1];
@@ -466,13 +759,38 @@ impl TeiHandler {
             println!("info string Warning: increment given but no base time");
         }
 
+        // the book move has to wait for a ponderhit too, so just search instead
+        if self.own_book
+            && !ponder
+            && moves_to_search.is_empty()
+            && self.pos.ply() < self.book_depth
+            && let Some(book) = &self.book
+            && let Some(mv) = book.probe(&self.pos, &mut self.rng)
+        {
+            println!("info string Book move");
+            println!("bestmove {}", mv);
+            return;
+        }
+
+        if moves_to_go.is_some() && our_time.is_none() {
+            println!("info string Warning: movestogo given but no base time");
+        }
+
         if let Some(our_time) = our_time {
             let our_inc = our_inc.unwrap_or(0.0);
-            limits.set_time_manager(our_time, our_inc);
+            limits.set_time_manager(our_time, our_inc, moves_to_go);
         }
 
         let max_depth = max_depth.unwrap_or(search::MAX_DEPTH).clamp(1, search::MAX_DEPTH);
 
+        if !moves_to_search.is_empty() {
+            print!("info string searchmoves:");
+            for mv in &moves_to_search {
+                print!(" {}", mv);
+            }
+            println!();
+        }
+
         self.searcher.start_search(
             &self.pos,
             &self.key_history,
@@ -481,13 +799,70 @@ impl TeiHandler {
             max_depth,
             &moves_to_search,
             &self.options,
+            ponder,
         );
     }
 
+    // synchronous, like perft
+    fn handle_go_tinue(&self, args: &[&str], start_time: Instant) {
+        let mut max_depth = DEFAULT_TINUE_DEPTH;
+        let mut max_nodes = DEFAULT_TINUE_NODES;
+
+        let valid = parse_settings(args, "tinue limit", |name, value| {
+            match name {
+                "depth" => max_depth = parse_value::<i32>(name, value)?.clamp(1, MAX_TINUE_DEPTH),
+                "nodes" => max_nodes = parse_value::<usize>(name, value)?.max(1),
+                _ => return Some(false),
+            }
+            Some(true)
+        });
+
+        if !valid {
+            return;
+        }
+
+        let (result, nodes) = solve_tinue(&self.pos, max_depth, max_nodes, self.searcher.tt_size_mib());
+
+        let time = start_time.elapsed().as_secs_f64();
+        let nps = (nodes as f64 / time) as usize;
+        let ms = (time * 1000.0) as usize;
+
+        match result {
+            TinueResult::Proven(depth, line) => {
+                print!(
+                    "info depth {} time {} nodes {} nps {} score mate {} pv",
+                    depth,
+                    ms,
+                    nodes,
+                    nps,
+                    (depth + 1) / 2
+                );
+                for mv in &line {
+                    print!(" {}", mv);
+                }
+                println!();
+                println!("bestmove {}", line[0]);
+            }
+            // no bestmove, there is nothing to play
+            TinueResult::Disproven => {
+                println!("info time {} nodes {} nps {}", ms, nodes, nps);
+                println!("info string no tinue within {} plies", max_depth);
+            }
+            TinueResult::Unknown => {
+                println!("info time {} nodes {} nps {}", ms, nodes, nps);
+                println!("info string no tinue found within {} nodes", max_nodes);
+            }
+        }
+    }
+
     fn handle_stop(&mut self) {
         self.searcher.stop();
     }
 
+    fn handle_ponderhit(&mut self) {
+        self.searcher.ponderhit();
+    }
+
     fn handle_move(&mut self, args: &[&str]) {
         if args.is_empty() {
             eprintln!("Missing move");
@@ -511,16 +886,51 @@ impl TeiHandler {
     }
 
     fn handle_d(&self) {
+        println!("{}", self.pos.render());
+        println!();
         println!("TPS: {}", self.pos.tps());
         println!("Key: {:016x}", self.pos.key());
 
-        let static_eval = static_eval(&self.pos);
+        let static_eval = static_eval(&self.pos, self.searcher.eval_params());
         let static_eval = match self.pos.stm() {
             Player::P1 => static_eval,
             Player::P2 => -static_eval,
         };
 
         println!("Static eval (P1-relative): {:+.2}", (static_eval as f64) / 100.0);
+
+        if let Some(network) = self.searcher.network() {
+            let mut acc = Accumulator::new();
+            acc.refresh(network, &self.pos);
+
+            let nnue_eval = network.evaluate(&acc, self.pos.stm()) * self.pos.stm().sign();
+            println!("NNUE eval (P1-relative): {:+.2}", (nnue_eval as f64) / 100.0);
+        }
+
+        for player in [Player::P1, Player::P2] {
+            if self.pos.has_road(player) {
+                println!("{:?} has a road", player);
+                continue;
+            }
+
+            let mut threats = self
+                .pos
+                .placement_threats(player)
+                .into_iter()
+                .map(|sq| sq.to_string())
+                .collect::<Vec<_>>();
+            threats.extend(self.pos.spread_threats(player).iter().map(|mv| mv.to_string()));
+
+            if threats.is_empty() {
+                threats.push("none".to_string());
+            }
+
+            println!("{:?} road threats: {}", player, threats.join(" "));
+        }
+    }
+
+    fn handle_eval(&self) {
+        println!("{}", eval_breakdown(&self.pos, self.searcher.eval_params()));
     }
 
     fn handle_perft(&self, args: &[&str]) {
@@ -554,6 +964,354 @@ impl TeiHandler {
 
         split_perft(&self.pos, depth);
     }
+
+    fn handle_perftbench(&self, args: &[&str]) {
+        if args.is_empty() {
+            eprintln!("Missing depth");
+            return;
+        }
+
+        let depth = match args[0].parse() {
+            Ok(depth) => depth,
+            Err(_) => {
+                eprintln!("Invalid depth '{}'", args[0]);
+                return;
+            }
+        };
+
+        perft_bench(&self.pos, depth);
+    }
+
+    fn handle_bench(&self, args: &[&str]) {
+        if self.searcher.is_searching() {
+            eprintln!("Search running");
+            return;
+        }
+
+        let depth = match args.first() {
+            Some(depth) => match depth.parse::<i32>() {
+                Ok(depth) if depth > 0 => depth,
+                _ => {
+                    eprintln!("Invalid depth '{}'", depth);
+                    return;
+                }
+            },
+            None => DEFAULT_BENCH_DEPTH,
+        };
+
+        run_bench(depth);
+    }
+
+    fn handle_perftsuite(&self, args: &[&str]) {
+        if self.searcher.is_searching() {
+            eprintln!("Search running");
+            return;
+        }
+
+        let mut config = PerftSuiteConfig {
+            file: None,
+            threads: DEFAULT_PERFT_SUITE_THREADS,
+        };
+
+        // the suite file may also be given bare, as long as it isn't named like a setting
+        let args = match args.first() {
+            Some(&first) if !matches!(first, "file" | "threads") => {
+                config.file = Some(first.to_owned());
+                &args[1..]
+            }
+            _ => args,
+        };
+
+        let valid = parse_settings(args, "perftsuite setting", |name, value| {
+            match name {
+                "file" => config.file = Some(value.to_owned()),
+                "threads" => config.threads = parse_value::<usize>(name, value)?.clamp(1, MAX_THREADS as usize),
+                _ => return Some(false),
+            }
+            Some(true)
+        });
+
+        if !valid {
+            return;
+        }
+
+        if let Err(err) = run_perft_suite(&config) {
+            eprintln!("Failed to run perft suite: {}", err);
+        }
+    }
+
+    fn handle_selfplay(&self, args: &[&str]) {
+        if self.searcher.is_searching() {
+            eprintln!("Search running");
+            return;
+        }
+
+        let mut config = SelfplayConfig {
+            game: self.config(),
+            games: 0,
+            opening_plies: DEFAULT_OPENING_PLIES,
+            max_plies: DEFAULT_MAX_PLIES,
+            seed: 0,
+            engines: [EngineConfig::default(), EngineConfig::default()],
+            out: None,
+        };
+
+        // engine settings apply to both engines until one is picked
+        let mut engines = 0..2;
+        let mut args = args;
+
+        loop {
+            let end = args
+                .iter()
+                .position(|&arg| arg == "engine1" || arg == "engine2")
+                .unwrap_or(args.len());
+
+            let valid = parse_settings(&args[..end], "selfplay setting", |name, value| {
+                match name {
+                    "games" => config.games = parse_value(name, value)?,
+                    "openplies" => config.opening_plies = parse_value(name, value)?,
+                    "maxplies" => config.max_plies = parse_value(name, value)?,
+                    "seed" => config.seed = parse_value(name, value)?,
+                    "out" => config.out = Some(value.to_owned()),
+                    "nodes" => {
+                        let nodes = parse_value(name, value)?;
+                        engines.clone().for_each(|idx| config.engines[idx].nodes = Some(nodes));
+                    }
+                    "movetime" => {
+                        let movetime = parse_value::<u64>(name, value)? as f64 / 1000.0;
+                        engines
+                            .clone()
+                            .for_each(|idx| config.engines[idx].movetime = Some(movetime));
+                    }
+                    "time" => {
+                        let time = parse_value::<u64>(name, value)? as f64 / 1000.0;
+                        engines.clone().for_each(|idx| config.engines[idx].time = Some(time));
+                    }
+                    "inc" => {
+                        let increment = parse_value::<u64>(name, value)? as f64 / 1000.0;
+                        engines
+                            .clone()
+                            .for_each(|idx| config.engines[idx].increment = increment);
+                    }
+                    "hash" => {
+                        let hash_mib = parse_value::<usize>(name, value)?.clamp(1, MAX_TT_SIZE_MIB);
+                        engines.clone().for_each(|idx| config.engines[idx].hash_mib = hash_mib);
+                    }
+                    "threads" => {
+                        let threads = parse_value::<u32>(name, value)?.clamp(1, MAX_THREADS);
+                        engines.clone().for_each(|idx| config.engines[idx].threads = threads);
+                    }
+                    "evalfile" => engines
+                        .clone()
+                        .for_each(|idx| config.engines[idx].network = Some(value.to_owned())),
+                    _ => return Some(false),
+                }
+                Some(true)
+            });
+
+            if !valid {
+                return;
+            }
+
+            let Some(&engine) = args.get(end) else {
+                break;
+            };
+
+            engines = if engine == "engine1" { 0..1 } else { 1..2 };
+            args = &args[end + 1..];
+        }
+
+        if config.games == 0 {
+            eprintln!("Missing game count");
+            return;
+        }
+
+        if let Some(idx) = config.engines.iter().position(|engine| !engine.has_limits()) {
+            eprintln!("Missing limits for engine{}", idx + 1);
+            return;
+        }
+
+        match run_selfplay(&config) {
+            Ok(stats) => {
+                println!(
+                    "Score of engine1 vs engine2: {} - {} - {} [{:.3}] {}",
+                    stats.wins,
+                    stats.losses,
+                    stats.draws,
+                    stats.score(),
+                    stats.games()
+                );
+                println!(
+                    "Elo difference: {:.1} +/- {:.1}, LOS: {:.1} %",
+                    stats.elo(),
+                    stats.elo_error(),
+                    stats.los() * 100.0
+                );
+            }
+            Err(err) => eprintln!("Failed to run selfplay: {}", err),
+        }
+    }
+
+    fn handle_datagen(&self, args: &[&str]) {
+        if self.searcher.is_searching() {
+            eprintln!("Search running");
+            return;
+        }
+
+        let mut config = DatagenConfig {
+            game: self.config(),
+            games: 0,
+            nodes: DEFAULT_DATAGEN_NODES,
+            opening_plies: DEFAULT_DATAGEN_OPENING_PLIES,
+            max_plies: DEFAULT_MAX_PLIES,
+            seed: 0,
+            hash_mib: DEFAULT_TT_SIZE_MIB,
+            format: DataFormat::Binary,
+            out: String::new(),
+        };
+
+        let valid = parse_settings(args, "datagen setting", |name, value| {
+            match name {
+                "games" => config.games = parse_value(name, value)?,
+                "nodes" => config.nodes = parse_value(name, value)?,
+                "openplies" => config.opening_plies = parse_value(name, value)?,
+                "maxplies" => config.max_plies = parse_value(name, value)?,
+                "seed" => config.seed = parse_value(name, value)?,
+                "hash" => config.hash_mib = parse_value::<usize>(name, value)?.clamp(1, MAX_TT_SIZE_MIB),
+                "format" => config.format = parse_value(name, value)?,
+                "out" => config.out = value.to_owned(),
+                _ => return Some(false),
+            }
+            Some(true)
+        });
+
+        if !valid {
+            return;
+        }
+
+        if config.games == 0 {
+            eprintln!("Missing game count");
+            return;
+        }
+
+        if config.out.is_empty() {
+            eprintln!("Missing output file");
+            return;
+        }
+
+        match run_datagen(&config) {
+            Ok(positions) => println!("Wrote {} positions to {}", positions, config.out),
+            Err(err) => eprintln!("Failed to write data: {}", err),
+        }
+    }
+
+    fn handle_tune(&self, args: &[&str]) {
+        if self.searcher.is_searching() {
+            eprintln!("Search running");
+            return;
+        }
+
+        let mut config = TuneConfig {
+            game: self.config(),
+            data: String::new(),
+            epochs: DEFAULT_TUNE_EPOCHS,
+            learning_rate: DEFAULT_TUNE_LEARNING_RATE,
+            k: None,
+            lambda: 1.0,
+            out: String::new(),
+        };
+
+        let valid = parse_settings(args, "tune setting", |name, value| {
+            match name {
+                "data" => config.data = value.to_owned(),
+                "epochs" => config.epochs = parse_value(name, value)?,
+                "lr" => config.learning_rate = parse_value(name, value)?,
+                "k" => config.k = Some(parse_value(name, value)?),
+                "lambda" => config.lambda = parse_value::<f64>(name, value)?.clamp(0.0, 1.0),
+                "out" => config.out = value.to_owned(),
+                _ => return Some(false),
+            }
+            Some(true)
+        });
+
+        if !valid {
+            return;
+        }
+
+        if config.data.is_empty() {
+            eprintln!("Missing data file");
+            return;
+        }
+
+        if config.out.is_empty() {
+            eprintln!("Missing output file");
+            return;
+        }
+
+        match run_tune(&config, self.searcher.eval_params()) {
+            Ok(_) => println!("Wrote tuned parameters to {}", config.out),
+            Err(err) => eprintln!("Failed to tune: {}", err),
+        }
+    }
+
+    fn handle_book(&self, args: &[&str]) {
+        if self.searcher.is_searching() {
+            eprintln!("Search running");
+            return;
+        }
+
+        match args.first() {
+            Some(&"build") => self.handle_book_build(&args[1..]),
+            Some(unknown) => eprintln!("Unknown book command '{}'", unknown),
+            None => eprintln!("Missing book command"),
+        }
+    }
+
+    fn handle_book_build(&self, args: &[&str]) {
+        let mut config = BookConfig {
+            size: self.size,
+            games: Vec::new(),
+            depth: DEFAULT_BOOK_DEPTH,
+            min_games: DEFAULT_BOOK_MIN_GAMES,
+            out: String::new(),
+        };
+
+        let valid = parse_settings(args, "book setting", |name, value| {
+            match name {
+                // may be given more than once
+                "games" => config.games.push(value.to_owned()),
+                "depth" => config.depth = parse_value::<u16>(name, value)?.min(MAX_BOOK_DEPTH),
+                "mingames" => config.min_games = parse_value::<u32>(name, value)?.max(1),
+                "out" => config.out = value.to_owned(),
+                _ => return Some(false),
+            }
+            Some(true)
+        });
+
+        if !valid {
+            return;
+        }
+
+        if config.games.is_empty() {
+            eprintln!("Missing game file");
+            return;
+        }
+
+        if config.out.is_empty() {
+            eprintln!("Missing output file");
+            return;
+        }
+
+        match build_book(&config) {
+            Ok(stats) => {
+                println!(
+                    "Read {} games ({} skipped), wrote {} moves in {} positions to {}",
+                    stats.games, stats.skipped, stats.entries, stats.positions, config.out
+                );
+            }
+            Err(err) => eprintln!("Failed to build book: {}", err),
+        }
+    }
 }
 
 pub fn run() {
@@ -22,20 +22,23 @@
  */
 
 use crate::board::FlatCountOutcome;
+use crate::eval::{EvalParams, static_eval};
 use crate::limit::Limits;
+use crate::nnue::{Accumulator, Network};
 use crate::node_counter::NodeCounter;
+use crate::observer::SearchObserver;
 use crate::tei::TeiOptions;
 use crate::ttable::{DEFAULT_TT_SIZE_MIB, TranspositionTable};
 use crate::{
-    board::Position,
+    board::{MoveUndo, Position},
     correction::CorrectionHistory,
     history::History,
     movepick::KillerTable,
-    search::{MAX_DEPTH, SCORE_INF, Score},
+    search::{MAX_DEPTH, SCORE_INF, Score, SearchResult},
     takmove::Move,
 };
-use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Instant;
 
 pub struct SearcherCount {
@@ -92,12 +95,21 @@ impl SearcherCount {
 
 pub struct SharedContext {
     pub tt: TranspositionTable,
+    pub eval_params: EvalParams,
+    // handcrafted eval if absent
+    pub network: Option<Box<Network>>,
     pub options: TeiOptions,
+    pub observer: Option<Arc<dyn SearchObserver>>,
     start_time: Instant,
     limits: Limits,
     stopped: AtomicBool,
     counter: Arc<SearcherCount>,
     nodes: NodeCounter,
+    result: Mutex<Option<SearchResult>>,
+    // nonzero while pondering, limits only apply after a ponderhit
+    pondering: AtomicU32,
+    // time spent pondering before the ponderhit, not counted against our limits
+    ponder_time_ns: AtomicU64,
 }
 
 impl SharedContext {
@@ -105,12 +117,18 @@ impl SharedContext {
         let time = Instant::now();
         Self {
             tt: TranspositionTable::new(DEFAULT_TT_SIZE_MIB),
+            eval_params: EvalParams::default(),
+            network: None,
             options: Default::default(),
+            observer: None,
             start_time: time,
             limits: Limits::new(time),
             stopped: AtomicBool::new(false),
             counter: Arc::new(SearcherCount::new()),
             nodes: NodeCounter::new(1),
+            result: Mutex::new(None),
+            pondering: AtomicU32::new(0),
+            ponder_time_ns: AtomicU64::new(0),
         }
     }
 
@@ -118,13 +136,17 @@ impl SharedContext {
         self.nodes.resize(threads as usize);
     }
 
-    pub fn init_search(&mut self, options: &TeiOptions, start_time: Instant, limits: Limits) {
+    pub fn init_search(&mut self, options: &TeiOptions, start_time: Instant, limits: Limits, ponder: bool) {
         self.options = *options;
         self.start_time = start_time;
         self.limits = limits;
         self.stopped.store(false, Ordering::Relaxed);
+        self.pondering.store(ponder as u32, Ordering::Relaxed);
+        self.ponder_time_ns.store(0, Ordering::Relaxed);
         self.counter.start();
         self.nodes.reset();
+        *self.result.get_mut().unwrap() = None;
+        self.tt.new_search();
     }
 
     pub fn get_counter(&self) -> Arc<SearcherCount> {
@@ -136,8 +158,15 @@ impl SharedContext {
     }
 
     pub fn check_stop_soft(&self, nodes: usize, best_move_nodes_fraction: f64) -> bool {
-        if self.limits.should_stop_soft(nodes, best_move_nodes_fraction) {
-            self.stopped.store(true, Ordering::Relaxed);
+        if self.is_pondering() {
+            return false;
+        }
+
+        if self
+            .limits
+            .should_stop_soft(nodes, best_move_nodes_fraction, self.ponder_time())
+        {
+            self.set_stopped();
             return true;
         }
 
@@ -145,14 +174,61 @@ impl SharedContext {
     }
 
     pub fn check_stop_hard(&self, nodes: usize) -> bool {
-        if self.limits.should_stop_hard(nodes) {
-            self.stopped.store(true, Ordering::Relaxed);
+        if self.is_pondering() {
+            return false;
+        }
+
+        if self.limits.should_stop_hard(nodes, self.ponder_time()) {
+            self.set_stopped();
             return true;
         }
 
         false
     }
 
+    #[must_use]
+    pub fn is_mate_found(&self, score: Score) -> bool {
+        self.limits.is_mate_found(score)
+    }
+
+    #[must_use]
+    pub fn is_pondering(&self) -> bool {
+        self.pondering.load(Ordering::Relaxed) != 0
+    }
+
+    #[must_use]
+    fn ponder_time(&self) -> f64 {
+        self.ponder_time_ns.load(Ordering::Relaxed) as f64 / 1_000_000_000.0
+    }
+
+    pub fn ponderhit(&self) {
+        // a stray ponderhit must not shift the limits of a normal search
+        if !self.is_pondering() {
+            return;
+        }
+
+        // recorded before clearing the flag, so limits never see the ponderhit without it
+        let ponder_time_ns = self.start_time.elapsed().as_nanos() as u64;
+        self.ponder_time_ns.store(ponder_time_ns, Ordering::Relaxed);
+
+        if self
+            .pondering
+            .compare_exchange(1, 0, Ordering::Release, Ordering::Relaxed)
+            .is_ok()
+        {
+            atomic_wait::wake_all(&self.pondering);
+        }
+    }
+
+    // the best move must not be reported until the opponent has actually played the pondered move
+    pub fn wait_for_ponderhit(&self) {
+        let mut pondering = self.pondering.load(Ordering::Acquire);
+        while pondering != 0 {
+            atomic_wait::wait(&self.pondering, pondering);
+            pondering = self.pondering.load(Ordering::Acquire);
+        }
+    }
+
     #[must_use]
     pub fn total_nodes(&self) -> usize {
         self.nodes.total()
@@ -163,10 +239,28 @@ impl SharedContext {
         self.start_time.elapsed().as_secs_f64()
     }
 
-    pub fn stop(&self) {
+    pub fn set_result(&self, result: SearchResult) {
+        *self.result.lock().unwrap() = Some(result);
+    }
+
+    #[must_use]
+    pub fn result(&self) -> Option<SearchResult> {
+        *self.result.lock().unwrap()
+    }
+
+    // ends the search from within, a ponder search still waits for the ponderhit before reporting
+    pub fn set_stopped(&self) {
         self.stopped.store(true, Ordering::Relaxed);
     }
 
+    // ends the search on request, stopping a ponder search reports its result immediately
+    pub fn stop(&self) {
+        self.set_stopped();
+
+        self.pondering.store(0, Ordering::Release);
+        atomic_wait::wake_all(&self.pondering);
+    }
+
     #[must_use]
     pub fn has_stopped(&self) -> bool {
         self.stopped.load(Ordering::Relaxed)
@@ -244,6 +338,8 @@ pub struct ThreadData {
     pub corrhist: Box<CorrectionHistory>,
     pub history: Box<History>,
     pub killers: [KillerTable; MAX_DEPTH as usize],
+    // indexed by ply, only kept up to date when a network is loaded
+    pub accumulators: Vec<Accumulator>,
     pub shared: Option<Arc<SharedContext>>,
 }
 
@@ -260,6 +356,7 @@ impl ThreadData {
             corrhist: CorrectionHistory::boxed(),
             history: History::boxed(),
             killers: [Default::default(); MAX_DEPTH as usize],
+            accumulators: vec![Accumulator::new(); MAX_DEPTH as usize + 2],
             shared: None,
         }
     }
@@ -303,20 +400,68 @@ impl ThreadData {
         self.root_moves[self.pv_idx..].sort_by_key(|mv| std::cmp::Reverse(mv.score));
     }
 
-    pub fn apply_move(&mut self, ply: i32, pos: &Position, mv: Move) -> Position {
+    #[must_use]
+    pub fn make_move(&mut self, ply: i32, pos: &mut Position, mv: Move) -> MoveUndo {
         self.key_history.push(pos.key());
         self.stack[ply as usize].mv = Some(mv);
-        pos.apply_move(mv)
+
+        let network = self.shared.as_deref().and_then(|shared| shared.network.as_deref());
+        let (prev, next) = self.accumulators.split_at_mut(ply as usize + 1);
+
+        if let Some(network) = network {
+            next[0].sub_squares(network, &prev[ply as usize], pos, mv.affected_squares());
+        }
+
+        let undo = pos.make_move(mv);
+
+        if let Some(network) = network {
+            next[0].add_squares(network, pos, mv.affected_squares());
+
+            #[cfg(debug_assertions)]
+            {
+                let mut refreshed = Accumulator::new();
+                refreshed.refresh(network, pos);
+                assert_eq!(next[0], refreshed);
+            }
+        }
+
+        undo
     }
 
-    pub fn apply_nullmove(&mut self, ply: i32, pos: &Position) -> Position {
+    pub fn unmake_move(&mut self, pos: &mut Position, undo: &MoveUndo) {
+        self.key_history.pop();
+        pos.unmake_move(undo);
+    }
+
+    pub fn make_nullmove(&mut self, ply: i32, pos: &mut Position) {
         self.key_history.push(pos.key());
         self.stack[ply as usize].mv = None;
-        pos.apply_nullmove()
+
+        if self.shared().network.is_some() {
+            self.accumulators[ply as usize + 1] = self.accumulators[ply as usize].clone();
+        }
+
+        pos.make_nullmove();
     }
 
-    pub fn pop_move(&mut self) {
+    pub fn unmake_nullmove(&mut self, pos: &mut Position) {
         self.key_history.pop();
+        pos.unmake_nullmove();
+    }
+
+    pub fn refresh_root_accumulator(&mut self, root_pos: &Position) {
+        if let Some(network) = self.shared.as_deref().and_then(|shared| shared.network.as_deref()) {
+            self.accumulators[0].refresh(network, root_pos);
+        }
+    }
+
+    // `pos` must be the position at `ply`
+    #[must_use]
+    pub fn evaluate(&self, pos: &Position, ply: i32) -> Score {
+        match self.shared().network.as_deref() {
+            Some(network) => network.evaluate(&self.accumulators[ply as usize], pos.stm()),
+            None => static_eval(pos, &self.shared().eval_params),
+        }
     }
 
     fn is_drawn_by_repetition(&self, curr: u64, ply: i32) -> bool {
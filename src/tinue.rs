@@ -0,0 +1,418 @@
+/*
+ * syntaks, a TEI Tak engine
+ * Copyright (c) 2026 Ciekce
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+// depth-first proof-number search for tinue, a forced road win for the side to move.
+// only moves that win outright or threaten to win next move are considered for the
+// attacker, and every reply for the defender. games ending in any other way are not tinue
+
+use crate::board::{FlatCountOutcome, Position};
+use crate::core::Player;
+use crate::movegen::generate_moves;
+use crate::takmove::Move;
+
+pub const DEFAULT_TINUE_DEPTH: i32 = 15;
+pub const MAX_TINUE_DEPTH: i32 = 127;
+
+pub const DEFAULT_TINUE_NODES: usize = 10_000_000;
+
+const THREAT_CACHE_SIZE: usize = 1 << 16;
+
+// proof and disproof numbers saturate here, well below overflow when summed
+const INF: u32 = u32::MAX / 4;
+
+#[derive(Copy, Clone, Debug, Default)]
+struct Entry {
+    key: u64,
+    pn: u32,
+    dn: u32,
+    // remaining plies the position was searched with, or the length of the proof
+    depth: u8,
+}
+
+// proof and disproof numbers don't fit in a scored search TT entry, so the solver gets its own
+// table of the same size for the duration of a solve. halved until it can be allocated
+struct ProofTable {
+    entries: Box<[Entry]>,
+}
+
+impl ProofTable {
+    #[must_use]
+    fn new(size_mib: usize) -> Self {
+        let mut entry_count = (size_mib * 1024 * 1024 / size_of::<Entry>()).max(1);
+
+        let mut entries = Vec::new();
+        while entries.try_reserve_exact(entry_count).is_err() && entry_count > 1 {
+            entry_count /= 2;
+        }
+
+        entries.resize(entry_count, Entry::default());
+
+        Self {
+            entries: entries.into_boxed_slice(),
+        }
+    }
+
+    #[must_use]
+    fn probe(&self, key: u64, depth: i32) -> Option<(u32, u32)> {
+        let entry = &self.entries[self.calc_index(key)];
+
+        if entry.key != key {
+            return None;
+        }
+
+        let entry_depth = entry.depth as i32;
+
+        // a proof holds with more plies to spare, a disproof with fewer
+        if entry.pn == 0 && depth >= entry_depth {
+            Some((0, INF))
+        } else if entry.dn == 0 && depth <= entry_depth {
+            Some((INF, 0))
+        } else if depth == entry_depth && entry.pn != 0 && entry.dn != 0 {
+            Some((entry.pn, entry.dn))
+        } else {
+            None
+        }
+    }
+
+    // the fewest remaining plies the position is known to be proven with
+    #[must_use]
+    fn proof_depth(&self, key: u64) -> Option<i32> {
+        let entry = &self.entries[self.calc_index(key)];
+        (entry.key == key && entry.pn == 0).then_some(entry.depth as i32)
+    }
+
+    fn store(&mut self, key: u64, depth: i32, pn: u32, dn: u32) {
+        let idx = self.calc_index(key);
+
+        self.entries[idx] = Entry {
+            key,
+            pn,
+            dn,
+            depth: depth as u8,
+        };
+    }
+
+    #[must_use]
+    fn calc_index(&self, key: u64) -> usize {
+        ((key as u128 * self.entries.len() as u128) >> 64) as usize
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+enum ChildValue {
+    Proven,
+    Open,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Child {
+    pos: Position,
+    value: ChildValue,
+}
+
+#[derive(Clone, Debug)]
+pub enum TinueResult {
+    // the shortest tinue in plies, and a line leading to it
+    Proven(i32, Vec<Move>),
+    // no tinue within the given plies
+    Disproven,
+    // ran out of nodes
+    Unknown,
+}
+
+struct Solver {
+    attacker: Player,
+    table: ProofTable,
+    // keys of the positions on the current line, to cut off repetitions
+    path: Vec<u64>,
+    // positions are expanded many times over, and threat detection is the expensive part.
+    // keys with the lowest bit replaced by whether there is a threat
+    threat_cache: Vec<u64>,
+    nodes: usize,
+    max_nodes: usize,
+}
+
+#[must_use]
+fn sum(a: u32, b: u32) -> u32 {
+    a.saturating_add(b).min(INF)
+}
+
+impl Solver {
+    #[must_use]
+    fn has_road_threat(&mut self, pos: &Position) -> bool {
+        let key = pos.key();
+        let idx = (key as usize) % THREAT_CACHE_SIZE;
+
+        let cached = self.threat_cache[idx];
+        if (cached ^ key) & !1 == 0 && cached != 0 {
+            return cached & 1 != 0;
+        }
+
+        // whether the side that just moved could win by road if it moved again
+        let threat = pos.has_road_threat(pos.stm().flip());
+        self.threat_cache[idx] = (key & !1) | threat as u64;
+
+        threat
+    }
+
+    // empty if the node is already decided, with the proof and disproof numbers to return
+    fn children(&mut self, pos: &Position, depth: i32) -> Result<Vec<Child>, (u32, u32)> {
+        let attacking = pos.stm() == self.attacker;
+        let defender = self.attacker.flip();
+
+        let mut moves = Vec::with_capacity(256);
+        generate_moves(&mut moves, pos);
+
+        let mut children = Vec::with_capacity(moves.len());
+
+        for &mv in &moves {
+            let next = pos.apply_move(mv);
+
+            // a move completing roads for both players wins for the mover
+            let attacker_road = next.has_road(self.attacker);
+            let defender_road = next.has_road(defender);
+
+            let value = if attacking {
+                if attacker_road {
+                    return Err((0, INF));
+                }
+
+                if defender_road
+                    || !matches!(next.count_flats(), FlatCountOutcome::None)
+                    || depth < 3
+                    || !self.has_road_threat(&next)
+                {
+                    continue;
+                }
+
+                ChildValue::Open
+            } else if defender_road {
+                return Err((INF, 0));
+            } else if attacker_road {
+                ChildValue::Proven
+            } else if !matches!(next.count_flats(), FlatCountOutcome::None) {
+                return Err((INF, 0));
+            } else {
+                ChildValue::Open
+            };
+
+            children.push(Child { pos: next, value });
+        }
+
+        if children.is_empty() {
+            // the attacker has no threats, and the defender always has a move
+            assert!(attacking);
+            return Err((INF, 0));
+        }
+
+        Ok(children)
+    }
+
+    fn mid(&mut self, pos: &Position, depth: i32, th_pn: u32, th_dn: u32) -> (u32, u32) {
+        self.nodes += 1;
+
+        let key = pos.key();
+        let attacking = pos.stm() == self.attacker;
+
+        let children = match self.children(pos, depth) {
+            Ok(children) => children,
+            Err((pn, dn)) => {
+                // an immediate road is a proof of one ply
+                let depth = if pn == 0 { 1 } else { depth };
+                self.table.store(key, depth, pn, dn);
+                return (pn, dn);
+            }
+        };
+
+        let mut values: Vec<_> = children
+            .iter()
+            .map(|child| match child.value {
+                ChildValue::Proven => (0, INF),
+                ChildValue::Open if self.path.contains(&child.pos.key()) => (INF, 0),
+                ChildValue::Open => self.table.probe(child.pos.key(), depth - 1).unwrap_or((1, 1)),
+            })
+            .collect();
+
+        self.path.push(key);
+
+        let (pn, dn) = loop {
+            // the attacker needs one proven move, the defender one disproven reply.
+            // n and m are pn and dn at attacker nodes, and the other way around at defender nodes
+            let mut best = 0;
+            let mut best_n = INF;
+            let mut second_n = INF;
+            let mut m_sum = 0;
+
+            for (idx, &value) in values.iter().enumerate() {
+                let (n, m) = if attacking { value } else { (value.1, value.0) };
+
+                m_sum = sum(m_sum, m);
+
+                if n < best_n {
+                    second_n = best_n;
+                    best_n = n;
+                    best = idx;
+                } else if n < second_n {
+                    second_n = n;
+                }
+            }
+
+            let (pn, dn) = if attacking { (best_n, m_sum) } else { (m_sum, best_n) };
+
+            if pn >= th_pn || dn >= th_dn || self.nodes >= self.max_nodes {
+                break (pn, dn);
+            }
+
+            let (th_n, th_m) = if attacking { (th_pn, th_dn) } else { (th_dn, th_pn) };
+            let best_m = if attacking { values[best].1 } else { values[best].0 };
+
+            let child_th_n = th_n.min(sum(second_n, 1));
+            let child_th_m = sum(th_m - m_sum, best_m);
+
+            let (child_th_pn, child_th_dn) = if attacking {
+                (child_th_n, child_th_m)
+            } else {
+                (child_th_m, child_th_n)
+            };
+
+            let child = children[best].pos;
+            values[best] = self.mid(&child, depth - 1, child_th_pn, child_th_dn);
+        };
+
+        self.path.pop();
+
+        // store proofs with their actual length, so they can be reused with fewer plies to spare
+        let depth = if pn == 0 {
+            self.proof_length(&children, &values, attacking, depth)
+        } else {
+            depth
+        };
+
+        self.table.store(key, depth, pn, dn);
+
+        (pn, dn)
+    }
+
+    #[must_use]
+    fn proof_length(&self, children: &[Child], values: &[(u32, u32)], attacking: bool, depth: i32) -> i32 {
+        let lengths = children
+            .iter()
+            .zip(values)
+            .filter(|(_, (pn, _))| *pn == 0)
+            .map(|(child, _)| match child.value {
+                ChildValue::Proven => 0,
+                // fall back to the most it could be if the child's entry was overwritten
+                ChildValue::Open => self
+                    .table
+                    .proof_depth(child.pos.key())
+                    .unwrap_or(depth - 1)
+                    .min(depth - 1),
+            });
+
+        let length = if attacking { lengths.min() } else { lengths.max() };
+
+        length.unwrap() + 1
+    }
+
+    // follows the quickest proven attacker moves, and the defender replies that hold out longest
+    #[must_use]
+    fn proof_line(&self, pos: &Position, depth: i32) -> Vec<Move> {
+        let mut line = Vec::new();
+        let mut moves = Vec::with_capacity(256);
+
+        let mut pos = *pos;
+
+        for depth in (1..=depth).rev() {
+            moves.clear();
+            generate_moves(&mut moves, &pos);
+
+            let attacking = pos.stm() == self.attacker;
+
+            let proof_depth = |mv: Move| {
+                let next = pos.apply_move(mv);
+                if next.has_road(self.attacker) {
+                    Some(0)
+                } else {
+                    self.table.proof_depth(next.key()).filter(|&proof| proof < depth)
+                }
+            };
+
+            let next = if attacking {
+                moves
+                    .iter()
+                    .filter_map(|&mv| proof_depth(mv).map(|proof| (mv, proof)))
+                    .min_by_key(|&(_, proof)| proof)
+            } else {
+                moves
+                    .iter()
+                    .filter_map(|&mv| proof_depth(mv).map(|proof| (mv, proof)))
+                    .max_by_key(|&(_, proof)| proof)
+            };
+
+            // entries needed for the rest of the line may have been overwritten
+            let Some((mv, _)) = next else {
+                break;
+            };
+
+            line.push(mv);
+            pos = pos.apply_move(mv);
+
+            if pos.has_road(self.attacker) {
+                break;
+            }
+        }
+
+        line
+    }
+}
+
+// iteratively deepens by two plies, so a proof is also the shortest tinue
+// the proof table is sized like the hash table and only lives for this call
+#[must_use]
+pub fn solve_tinue(pos: &Position, max_depth: i32, max_nodes: usize, table_mib: usize) -> (TinueResult, usize) {
+    let mut solver = Solver {
+        attacker: pos.stm(),
+        table: ProofTable::new(table_mib),
+        path: Vec::with_capacity(max_depth as usize),
+        threat_cache: vec![0; THREAT_CACHE_SIZE],
+        nodes: 0,
+        max_nodes,
+    };
+
+    let max_depth = max_depth.clamp(1, MAX_TINUE_DEPTH);
+
+    for depth in (1..=max_depth).step_by(2) {
+        let (pn, dn) = solver.mid(pos, depth, INF, INF);
+
+        if pn == 0 {
+            let line = solver.proof_line(pos, depth);
+            return (TinueResult::Proven(depth, line), solver.nodes);
+        }
+
+        if dn != 0 {
+            return (TinueResult::Unknown, solver.nodes);
+        }
+    }
+
+    (TinueResult::Disproven, solver.nodes)
+}
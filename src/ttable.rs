@@ -23,13 +23,31 @@
 
 use crate::search::{Score, is_loss, is_win};
 use crate::takmove::Move;
+use std::alloc::Layout;
+#[cfg(target_arch = "x86_64")]
 use std::arch::x86_64::{_MM_HINT_T0, _mm_prefetch};
+use std::fmt::{Display, Formatter};
 use std::mem::MaybeUninit;
+use std::ops::Deref;
+use std::ptr::NonNull;
 use std::sync::atomic::{AtomicU64, Ordering};
 
 pub const DEFAULT_TT_SIZE_MIB: usize = 64;
 pub const MAX_TT_SIZE_MIB: usize = 131072;
 
+const HUGE_PAGE_SIZE: usize = 2 * 1024 * 1024;
+
+#[derive(Debug)]
+pub struct TtAllocError {
+    pub size_mib: usize,
+}
+
+impl Display for TtAllocError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "failed to allocate {} MiB for the hash table", self.size_mib)
+    }
+}
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 #[repr(u8)]
 pub enum TtFlag {
@@ -38,14 +56,76 @@ pub enum TtFlag {
     Exact,
 }
 
+impl TtFlag {
+    #[must_use]
+    fn from_raw(raw: u8) -> Option<Self> {
+        match raw {
+            1 => Some(Self::UpperBound),
+            2 => Some(Self::LowerBound),
+            3 => Some(Self::Exact),
+            _ => None,
+        }
+    }
+}
+
+const FLAG_BITS: u32 = 2;
+const FLAG_MASK: u8 = (1 << FLAG_BITS) - 1;
+
+// searches wrap around after this many generations
+const AGE_BITS: u32 = 3;
+const AGE_CYCLE: u8 = 1 << AGE_BITS;
+const AGE_MASK: u8 = AGE_CYCLE - 1;
+
+// the rest of the flag byte extends the key, so that probing a whole
+// cluster doesn't make false hits any likelier than a single entry did
+const KEY_BITS: u32 = 16 + (8 - FLAG_BITS - AGE_BITS);
+
+// one cache line
+const CLUSTER_SIZE: usize = 8;
+
 #[derive(Copy, Clone, Debug, Default)]
 #[repr(C)]
 struct Entry {
+    // low key bits
     key: u16,
     score: i16,
     mv: Option<Move>,
     depth: u8,
-    flag: Option<TtFlag>,
+    // flag in the low bits, then the age of the search that stored it, then the high key bits
+    flag_age: u8,
+}
+
+impl Entry {
+    #[must_use]
+    fn flag(&self) -> Option<TtFlag> {
+        TtFlag::from_raw(self.flag_age & FLAG_MASK)
+    }
+
+    #[must_use]
+    fn age(&self) -> u8 {
+        (self.flag_age >> FLAG_BITS) & AGE_MASK
+    }
+
+    #[must_use]
+    fn key(&self) -> u32 {
+        self.key as u32 | ((self.flag_age >> (FLAG_BITS + AGE_BITS)) as u32) << 16
+    }
+
+    #[must_use]
+    fn matches(&self, key: u32) -> bool {
+        self.key() == key && self.flag().is_some()
+    }
+
+    // how many searches ago this entry was stored
+    #[must_use]
+    fn relative_age(&self, age: u8) -> i32 {
+        (age.wrapping_sub(self.age()) & AGE_MASK) as i32
+    }
+
+    fn set_key_flag_age(&mut self, key: u32, flag: TtFlag, age: u8) {
+        self.key = key as u16;
+        self.flag_age = (((key >> 16) as u8) << (FLAG_BITS + AGE_BITS)) | (age << FLAG_BITS) | flag as u8;
+    }
 }
 
 #[derive(Debug, Default)]
@@ -66,6 +146,14 @@ impl EntryStorage {
     }
 }
 
+#[derive(Debug, Default)]
+#[repr(C, align(64))]
+struct Cluster {
+    entries: [EntryStorage; CLUSTER_SIZE],
+}
+
+const _: () = assert!(size_of::<Cluster>() == 64);
+
 #[derive(Copy, Clone, Debug, Default)]
 pub struct ProbedEntry {
     pub score: Score,
@@ -75,13 +163,13 @@ pub struct ProbedEntry {
 }
 
 #[must_use]
-fn calc_entry_count(size_mib: usize) -> usize {
-    size_mib * 1024 * 1024 / size_of::<Entry>()
+fn calc_cluster_count(size_mib: usize) -> usize {
+    size_mib * 1024 * 1024 / size_of::<Cluster>()
 }
 
 #[must_use]
-fn pack_entry_key(key: u64) -> u16 {
-    key as u16
+fn pack_entry_key(key: u64) -> u32 {
+    key as u32 & ((1 << KEY_BITS) - 1)
 }
 
 #[must_use]
@@ -132,40 +220,118 @@ unsafe fn clear_threaded<T: Send>(ptr: *mut MaybeUninit<T>, len: usize, threads:
     });
 }
 
+// zeroed clusters, aligned to and backed by huge pages when large enough and the os allows
+struct ClusterArray {
+    ptr: NonNull<Cluster>,
+    len: usize,
+    layout: Layout,
+}
+
+//SAFETY: clusters are only accessed atomically through shared references
+unsafe impl Send for ClusterArray {}
+unsafe impl Sync for ClusterArray {}
+
+impl ClusterArray {
+    #[must_use]
+    fn new(len: usize, threads: usize) -> Option<Self> {
+        assert!(len > 0);
+
+        let size = len.checked_mul(size_of::<Cluster>())?;
+
+        let alloc = |align: usize| {
+            let layout = Layout::from_size_align(size, align).ok()?;
+            //SAFETY: layout has a nonzero size
+            let ptr = NonNull::new(unsafe { std::alloc::alloc(layout) }.cast::<Cluster>())?;
+            Some((ptr, layout))
+        };
+
+        // huge page alignment wastes up to a huge page, so fall back to plain alignment if it fails
+        let (ptr, layout) = if size >= HUGE_PAGE_SIZE {
+            alloc(HUGE_PAGE_SIZE).or_else(|| alloc(align_of::<Cluster>()))?
+        } else {
+            alloc(align_of::<Cluster>())?
+        };
+
+        // only a hint, nothing to do if thp is unavailable
+        #[cfg(target_os = "linux")]
+        if layout.align() == HUGE_PAGE_SIZE {
+            //SAFETY: the range was just allocated
+            unsafe { libc::madvise(ptr.as_ptr().cast(), size, libc::MADV_HUGEPAGE) };
+        }
+
+        //SAFETY: all-zeroes is a valid bitpattern for Cluster, and the range is fully in bounds
+        unsafe { clear_threaded::<Cluster>(ptr.as_ptr().cast(), len, threads) };
+
+        Some(Self { ptr, len, layout })
+    }
+
+    fn clear(&mut self, threads: usize) {
+        //SAFETY: all-zeroes is a valid bitpattern for Cluster, and the range is fully in bounds
+        unsafe { clear_threaded::<Cluster>(self.ptr.as_ptr().cast(), self.len, threads) };
+    }
+}
+
+impl Deref for ClusterArray {
+    type Target = [Cluster];
+
+    fn deref(&self) -> &Self::Target {
+        //SAFETY: the whole range was zeroed on allocation
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl Drop for ClusterArray {
+    fn drop(&mut self) {
+        //SAFETY: allocated in new() with this layout
+        unsafe { std::alloc::dealloc(self.ptr.as_ptr().cast(), self.layout) };
+    }
+}
+
 pub struct TranspositionTable {
-    entries: Vec<EntryStorage>,
+    clusters: ClusterArray,
+    age: u8,
 }
 
 impl TranspositionTable {
+    // halves the size until the allocation succeeds, as there is no previous table to keep
     #[must_use]
     pub fn new(size_mib: usize) -> TranspositionTable {
         assert!(size_mib > 0);
 
-        let mut result = Self {
-            entries: Vec::default(),
-        };
+        let mut len = calc_cluster_count(size_mib);
+
+        let clusters = loop {
+            if let Some(clusters) = ClusterArray::new(len, 1) {
+                break clusters;
+            }
+
+            if len == 1 {
+                std::alloc::handle_alloc_error(Layout::new::<Cluster>());
+            }
 
-        result.resize(size_mib, 1);
+            len /= 2;
+        };
 
-        result
+        Self { clusters, age: 0 }
     }
 
-    pub fn resize(&mut self, size_mib: usize, threads: usize) {
+    // keeps the current table if the new one cannot be allocated,
+    // so both exist at once while resizing
+    pub fn resize(&mut self, size_mib: usize, threads: usize) -> Result<(), TtAllocError> {
         assert!(size_mib > 0);
         assert!(threads > 0);
 
-        // ensure the entire old TT is deallocated
-        self.entries = Vec::new();
+        let clusters = ClusterArray::new(calc_cluster_count(size_mib), threads).ok_or(TtAllocError { size_mib })?;
 
-        let entry_count = calc_entry_count(size_mib);
-        self.entries = Vec::with_capacity(entry_count);
+        self.clusters = clusters;
+        self.age = 0;
 
-        unsafe {
-            //SAFETY: all-zeroes is a valid bitpattern for EntryStorage, and the range is fully in bounds
-            clear_threaded::<EntryStorage>(self.entries.as_mut_ptr().cast(), entry_count, threads);
-            //SAFETY: we just initialised these values
-            self.entries.set_len(entry_count);
-        }
+        Ok(())
+    }
+
+    // called before each search, so that entries from previous ones are replaced first
+    pub fn new_search(&mut self) {
+        self.age = (self.age + 1) & AGE_MASK;
     }
 
     pub fn prefetch(&self, key: u64) {
@@ -173,10 +339,13 @@ impl TranspositionTable {
         {
             let idx = self.calc_index(key);
             //SAFETY: calc_index() cannot return an out-of-bounds index
-            let entry = unsafe { self.entries.get_unchecked(idx) };
-            let ptr = std::ptr::from_ref(entry).cast();
+            let cluster = unsafe { self.clusters.get_unchecked(idx) };
+            let ptr = std::ptr::from_ref(cluster).cast();
             unsafe { _mm_prefetch(ptr, _MM_HINT_T0) };
         }
+
+        #[cfg(not(target_arch = "x86_64"))]
+        let _ = key;
     }
 
     #[must_use]
@@ -184,21 +353,25 @@ impl TranspositionTable {
         let idx = self.calc_index(key);
         let entry_key = pack_entry_key(key);
 
-        let mut probed = Default::default();
-
         //SAFETY: calc_index() cannot return an out-of-bounds index
-        let entry = unsafe { self.entries.get_unchecked(idx) }.load();
+        let cluster = unsafe { self.clusters.get_unchecked(idx) };
 
-        if entry.key != entry_key {
-            return (false, probed);
-        }
+        for storage in &cluster.entries {
+            let entry = storage.load();
 
-        probed.score = score_from_tt(entry.score, ply);
-        probed.mv = entry.mv;
-        probed.depth = entry.depth as i32;
-        probed.flag = entry.flag;
+            if entry.matches(entry_key) {
+                let probed = ProbedEntry {
+                    score: score_from_tt(entry.score, ply),
+                    mv: entry.mv,
+                    depth: entry.depth as i32,
+                    flag: entry.flag(),
+                };
 
-        (true, probed)
+                return (true, probed);
+            }
+        }
+
+        (false, Default::default())
     }
 
     pub fn store(&self, key: u64, score: Score, mv: Option<Move>, depth: i32, ply: i32, flag: TtFlag) {
@@ -206,37 +379,68 @@ impl TranspositionTable {
         let entry_key = pack_entry_key(key);
 
         //SAFETY: calc_index() cannot return an out-of-bounds index
-        let storage = unsafe { self.entries.get_unchecked(idx) };
-
-        let mut entry = storage.load();
+        let cluster = unsafe { self.clusters.get_unchecked(idx) };
+
+        // an entry for this position if there is one, otherwise an empty one,
+        // otherwise whichever is least worth keeping - shallow, and from old searches
+        let (storage, mut entry) = cluster
+            .entries
+            .iter()
+            .map(|storage| (storage, storage.load()))
+            .min_by_key(|(_, entry)| {
+                if entry.matches(entry_key) {
+                    i32::MIN
+                } else if entry.flag().is_none() {
+                    i32::MIN + 1
+                } else {
+                    entry.depth as i32 - 4 * entry.relative_age(self.age)
+                }
+            })
+            .unwrap();
+
+        let same_position = entry.matches(entry_key);
+
+        // don't overwrite a deeper bound on this position from the current search
+        if same_position && flag != TtFlag::Exact && entry.age() == self.age && depth + 4 <= entry.depth as i32 {
+            return;
+        }
 
-        if mv.is_some() || entry.key != entry_key {
+        if mv.is_some() || !same_position {
             entry.mv = mv;
         }
 
-        entry.key = entry_key;
         entry.score = score_to_tt(score, ply);
         entry.depth = depth as u8;
-        entry.flag = Some(flag);
+        entry.set_key_flag_age(entry_key, flag, self.age);
 
         storage.store(entry);
     }
 
+    // the actual size, which can be smaller than requested if allocation failed
+    #[must_use]
+    pub fn size_mib(&self) -> usize {
+        (self.clusters.len() * size_of::<Cluster>() / (1024 * 1024)).max(1)
+    }
+
     pub fn clear(&mut self, threads: usize) {
         assert!(threads > 0);
 
-        //SAFETY: all-zeroes is a valid bitpattern for EntryStorage, and the range is fully in bounds
-        unsafe { clear_threaded::<EntryStorage>(self.entries.as_mut_ptr().cast(), self.entries.len(), threads) };
+        self.clusters.clear(threads);
+
+        self.age = 0;
     }
 
     #[must_use]
     pub fn estimate_full_permille(&self) -> usize {
         let mut filled = 0;
 
-        for storage in self.entries[0..1000].iter() {
-            let entry = storage.load();
-            if entry.flag.is_some() {
-                filled += 1;
+        // 1000 entries, only counting those from the current search
+        for cluster in self.clusters[0..1000 / CLUSTER_SIZE].iter() {
+            for storage in &cluster.entries {
+                let entry = storage.load();
+                if entry.flag().is_some() && entry.age() == self.age {
+                    filled += 1;
+                }
             }
         }
 
@@ -245,6 +449,6 @@ impl TranspositionTable {
 
     #[must_use]
     fn calc_index(&self, key: u64) -> usize {
-        ((key as u128 * self.entries.len() as u128) >> 64) as usize
+        ((key as u128 * self.clusters.len() as u128) >> 64) as usize
     }
 }
@@ -0,0 +1,341 @@
+/*
+ * syntaks, a TEI Tak engine
+ * Copyright (c) 2026 Ciekce
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::board::{GameConfig, Position};
+use crate::eval::{DEFAULT_EVAL_PARAMS, EvalAccumulator, EvalParams, Param, evaluate};
+use crate::search::Score;
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::time::Instant;
+
+pub const DEFAULT_TUNE_EPOCHS: usize = 500;
+pub const DEFAULT_TUNE_LEARNING_RATE: f64 = 0.5;
+
+const ADAM_BETA1: f64 = 0.9;
+const ADAM_BETA2: f64 = 0.999;
+const ADAM_EPSILON: f64 = 1e-8;
+
+const REPORT_INTERVAL: usize = 25;
+
+#[derive(Clone, Debug)]
+pub struct TuneConfig {
+    pub game: GameConfig,
+    pub data: String,
+    pub epochs: usize,
+    pub learning_rate: f64,
+    // sigmoid scale, fitted to the data with the current parameters if not given
+    pub k: Option<f64>,
+    // weight of the game result in the target, the rest is the search score
+    pub lambda: f64,
+    pub out: String,
+}
+
+#[derive(Debug)]
+pub enum TuneError {
+    Io(std::io::Error),
+    // the line of the offending position
+    InvalidEntry(usize),
+    // the line of a position generated with another size, reserves or komi
+    WrongConfig(usize),
+    NoPositions,
+}
+
+impl Display for TuneError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{}", err),
+            Self::InvalidEntry(line) => write!(f, "invalid position on line {}", line),
+            Self::WrongConfig(line) => write!(f, "position on line {} is from a different game config", line),
+            Self::NoPositions => write!(f, "no positions"),
+        }
+    }
+}
+
+impl From<std::io::Error> for TuneError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+// p1-relative coefficients of every parameter in a position's eval
+struct TraceAccumulator {
+    coefficients: [f64; Param::COUNT],
+}
+
+impl EvalAccumulator for TraceAccumulator {
+    fn add(&mut self, param: Param, count: i32) {
+        self.coefficients[param.idx()] += count as f64;
+    }
+
+    fn add_halves(&mut self, param: Param, half_count: i32) {
+        self.coefficients[param.idx()] += half_count as f64 / 2.0;
+    }
+}
+
+struct Entry {
+    coefficients: Vec<(u16, f32)>,
+    // p1-relative
+    score: Option<Score>,
+    result: f64,
+}
+
+impl Entry {
+    #[must_use]
+    fn eval(&self, params: &[f64]) -> f64 {
+        self.coefficients
+            .iter()
+            .map(|&(idx, coefficient)| params[idx as usize] * coefficient as f64)
+            .sum()
+    }
+}
+
+#[must_use]
+fn sigmoid(x: f64) -> f64 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+// "<tps> | <score> | <result> | <size> <flats> <caps> <half komi>" as written by datagen,
+// or "<tps> | <result>" for positions from elsewhere, which are assumed to match the config
+fn parse_entry(config: GameConfig, line: &str, line_number: usize) -> Result<Entry, TuneError> {
+    let fields: Vec<&str> = line.split('|').map(str::trim).collect();
+
+    let (tps, score, result, game) = match fields[..] {
+        [tps, result] => (tps, None, result, None),
+        [tps, score, result] => (tps, Some(score), result, None),
+        [tps, score, result, game] => (tps, Some(score), result, Some(game)),
+        _ => return Err(TuneError::InvalidEntry(line_number)),
+    };
+
+    if let Some(game) = game {
+        let values = game
+            .split_ascii_whitespace()
+            .map(|value| value.parse::<u8>().ok())
+            .collect::<Option<Vec<_>>>()
+            .ok_or(TuneError::InvalidEntry(line_number))?;
+
+        if values[..] != [config.size.raw(), config.flats, config.caps, config.half_komi] {
+            return Err(TuneError::WrongConfig(line_number));
+        }
+    }
+
+    let score = match score {
+        Some(score) => Some(score.parse().map_err(|_| TuneError::InvalidEntry(line_number))?),
+        None => None,
+    };
+
+    let result = result
+        .parse::<f64>()
+        .ok()
+        .filter(|result| (0.0..=1.0).contains(result))
+        .ok_or(TuneError::InvalidEntry(line_number))?;
+
+    let parts: Vec<&str> = tps.split_ascii_whitespace().collect();
+    let pos = Position::from_tps_parts(config, &parts).map_err(|_| TuneError::InvalidEntry(line_number))?;
+
+    let mut acc = TraceAccumulator {
+        coefficients: [0.0; Param::COUNT],
+    };
+
+    evaluate(&pos, &mut acc);
+
+    let sign = pos.stm().sign() as f64;
+
+    let coefficients = acc
+        .coefficients
+        .iter()
+        .enumerate()
+        .filter(|&(_, &coefficient)| coefficient != 0.0)
+        .map(|(idx, &coefficient)| (idx as u16, (coefficient * sign) as f32))
+        .collect();
+
+    Ok(Entry {
+        coefficients,
+        score,
+        result,
+    })
+}
+
+fn load_entries(config: &TuneConfig) -> Result<Vec<Entry>, TuneError> {
+    let reader = BufReader::new(File::open(&config.data)?);
+
+    let mut entries = Vec::new();
+
+    for (line_idx, line) in reader.lines().enumerate() {
+        let line = line?;
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        entries.push(parse_entry(config.game, &line, line_idx + 1)?);
+    }
+
+    if entries.is_empty() {
+        return Err(TuneError::NoPositions);
+    }
+
+    Ok(entries)
+}
+
+#[must_use]
+fn targets(entries: &[Entry], k: f64, lambda: f64) -> Vec<f64> {
+    entries
+        .iter()
+        .map(|entry| match entry.score {
+            Some(score) => lambda * entry.result + (1.0 - lambda) * sigmoid(k * score as f64),
+            None => entry.result,
+        })
+        .collect()
+}
+
+#[must_use]
+fn error(entries: &[Entry], targets: &[f64], params: &[f64], k: f64) -> f64 {
+    let total: f64 = entries
+        .iter()
+        .zip(targets)
+        .map(|(entry, &target)| (sigmoid(k * entry.eval(params)) - target).powi(2))
+        .sum();
+
+    total / entries.len() as f64
+}
+
+// golden section search on the result-only error
+#[must_use]
+fn fit_k(entries: &[Entry], params: &[f64]) -> f64 {
+    let targets = targets(entries, 0.0, 1.0);
+
+    let ratio = (5.0f64.sqrt() - 1.0) / 2.0;
+
+    let mut low = 0.0001;
+    let mut high = 0.05;
+
+    for _ in 0..40 {
+        let a = high - ratio * (high - low);
+        let b = low + ratio * (high - low);
+
+        if error(entries, &targets, params, a) < error(entries, &targets, params, b) {
+            high = b;
+        } else {
+            low = a;
+        }
+    }
+
+    (low + high) / 2.0
+}
+
+fn write_rust_source(path: &str, params: &EvalParams) -> std::io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+
+    writeln!(out, "// generated by the tuner, use with `EvalParams::from_values`")?;
+    writeln!(out, "#[rustfmt::skip]")?;
+    writeln!(out, "pub const TUNED_EVAL_PARAMS: [Score; Param::COUNT] = [")?;
+
+    for (idx, value) in params.values().iter().enumerate() {
+        match Param::from_idx(idx) {
+            Some(param) => writeln!(out, "    {}, // {}", value, param)?,
+            None => writeln!(out, "    {},", value)?,
+        }
+    }
+
+    writeln!(out, "];")?;
+
+    out.flush()
+}
+
+// writes the tuned parameters to the output file, and as rust source next to it
+pub fn run_tune(config: &TuneConfig, initial: &EvalParams) -> Result<EvalParams, TuneError> {
+    let start_time = Instant::now();
+
+    let entries = load_entries(config)?;
+    println!(
+        "Loaded {} positions in {:.1}s",
+        entries.len(),
+        start_time.elapsed().as_secs_f64()
+    );
+
+    let mut params: Vec<f64> = initial.values().iter().map(|&value| value as f64).collect();
+
+    let k = config.k.unwrap_or_else(|| fit_k(&entries, &params));
+    println!("K: {:.6}", k);
+
+    let targets = targets(&entries, k, config.lambda);
+
+    println!("Initial error: {:.6}", error(&entries, &targets, &params, k));
+
+    let mut momentum = vec![0.0; Param::COUNT];
+    let mut velocity = vec![0.0; Param::COUNT];
+
+    let mut gradient = vec![0.0; Param::COUNT];
+
+    for epoch in 1..=config.epochs {
+        gradient.fill(0.0);
+
+        for (entry, &target) in entries.iter().zip(&targets) {
+            let predicted = sigmoid(k * entry.eval(&params));
+            let factor = (predicted - target) * predicted * (1.0 - predicted);
+
+            for &(idx, coefficient) in &entry.coefficients {
+                gradient[idx as usize] += factor * coefficient as f64;
+            }
+        }
+
+        let scale = 2.0 * k / entries.len() as f64;
+
+        let momentum_correction = 1.0 - ADAM_BETA1.powi(epoch as i32);
+        let velocity_correction = 1.0 - ADAM_BETA2.powi(epoch as i32);
+
+        for idx in 0..Param::COUNT {
+            let grad = gradient[idx] * scale;
+
+            momentum[idx] = ADAM_BETA1 * momentum[idx] + (1.0 - ADAM_BETA1) * grad;
+            velocity[idx] = ADAM_BETA2 * velocity[idx] + (1.0 - ADAM_BETA2) * grad * grad;
+
+            let m = momentum[idx] / momentum_correction;
+            let v = velocity[idx] / velocity_correction;
+
+            params[idx] -= config.learning_rate * m / (v.sqrt() + ADAM_EPSILON);
+        }
+
+        if epoch % REPORT_INTERVAL == 0 || epoch == config.epochs {
+            println!(
+                "Epoch {}: error {:.6}, {:.1}s",
+                epoch,
+                error(&entries, &targets, &params, k),
+                start_time.elapsed().as_secs_f64()
+            );
+        }
+    }
+
+    let mut values = *DEFAULT_EVAL_PARAMS.values();
+    for (value, &param) in values.iter_mut().zip(&params) {
+        *value = param.round() as Score;
+    }
+
+    let tuned = EvalParams::from_values(values);
+
+    std::fs::write(&config.out, tuned.to_string())?;
+    write_rust_source(&format!("{}.rs", config.out), &tuned)?;
+
+    Ok(tuned)
+}
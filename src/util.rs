@@ -22,3 +22,4 @@
  */
 
 pub mod command_channel;
+pub mod rng;